@@ -1,13 +1,104 @@
 use egui::Color32;
+use serde::{Deserialize, Serialize};
 
-use crate::calculation::{OptimalPositions, find_optimal_pickup_positions};
+use crate::blend::BlendPickup;
+use crate::calculation::{
+    DEFAULT_NODE_WARNING_WEIGHT, FretMode, Objective, OptimalPositions, ScoreModel, StringConfig,
+    find_optimal_pickup_positions, nearby_nodes,
+};
+use crate::color::{Colormap, HeatPalette};
+use crate::geometry::{NeckGeometry, ReferenceFrame};
+use crate::heat_map::{BlendHeatMap, HeatMap, HeatTexture, ScaleSweepHeatMap, StringSetHeatMap};
+use crate::history::{History, Params};
+use crate::humbucker::{
+    HumbuckerPositions, coil_spacing_attenuation, find_optimal_humbucker_positions,
+};
+use crate::instruments::{INSTRUMENTS, Instrument};
+use crate::spectrum::{MeasuredSpectrum, import_spectrum};
+use crate::stiffness::{Material, StringStiffness, inharmonicity};
+use crate::string_set::{PRESETS, StringSpec, WrapMaterial, note_name};
+use crate::synth::{SynthSettings, render_pluck, write_wav};
+use crate::units::{LengthUnit, length_input};
+use crate::visualizer::{VisualizerLayout, ZoomRange};
+
+const CONTROLS_DEFAULT_WIDTH: f32 = 420.0;
+const CONTROLS_MIN_WIDTH: f32 = 280.0;
+const CONTROLS_MAX_WIDTH: f32 = 720.0;
+
+/// Scale lengths accepted anywhere one is entered, from sopranino ukulele to extended bass
+const MIN_SCALE_LENGTH: f32 = 200.0;
+const MAX_SCALE_LENGTH: f32 = 1300.0;
+
+/// Narrowest scale length sweep the sweep controls allow, in mm
+const MIN_SWEEP_SPAN: f32 = 10.0;
+
+/// What is saved between sessions: the parameters plus how the window was arranged
+#[derive(Serialize, Deserialize)]
+struct SavedState {
+    params: Params,
+    view: VisualizerView,
+    layout: VisualizerLayout,
+    controls_open: bool,
+    unit: LengthUnit,
+    colormap: Colormap,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) enum VisualizerView {
+    SingleString,
+    StringSet,
+    ScaleSweep,
+    Blend,
+}
 
 pub(crate) struct HarmonicApp {
     pub(crate) string_length: f32,
     pub(crate) weights: [f32; 6], // Weights for harmonics 2-7
+    pub(crate) fret_mode: FretMode,
+    pub(crate) objective: Objective,
+    pub(crate) stiffness_enabled: bool,
+    pub(crate) stiffness: StringStiffness,
     pub(crate) optimal_positions: OptimalPositions,
+    pub(crate) ideal_positions: OptimalPositions, // Optimum for a perfectly flexible string
+    pub(crate) humbucker_enabled: bool,
+    pub(crate) coil_spacing: f32, // Distance between coil centers in mm
+    pub(crate) humbucker_positions: HumbuckerPositions,
+    pub(crate) node_warning_distance: f32, // mm
+    pub(crate) node_warning_weight: f32,
+    pub(crate) geometry: NeckGeometry,
+    pub(crate) marker_position: Option<f32>, // User-placed position in mm from bridge
+    pub(crate) tuning_frequency: f32,        // Open-string pitch in Hz
+    pub(crate) show_response: bool,
+    pub(crate) synth: SynthSettings,
+    pub(crate) export_directory: String,
+    pub(crate) export_status: Option<String>,
+    pub(crate) import_path: String,
+    pub(crate) import_status: Option<String>,
+    pub(crate) measured_spectrum: Option<MeasuredSpectrum>,
     pub(crate) heat_map_resolution: usize,
     pub(crate) search_limit: usize,
+    pub(crate) heat_map: HeatMap,
+    pub(crate) colormap: Colormap,
+    pub(crate) heat_palette: HeatPalette,
+    pub(crate) heat_texture: HeatTexture,
+    pub(crate) view: VisualizerView,
+    pub(crate) controls_open: bool,
+    pub(crate) layout: VisualizerLayout,
+    pub(crate) zoom: ZoomRange,
+    pub(crate) unit: LengthUnit,
+    pub(crate) history: History,
+    pub(crate) strings: Vec<StringConfig>,
+    pub(crate) string_specs: Vec<StringSpec>, // Highest string first, matching `strings`
+    pub(crate) string_optima: Vec<OptimalPositions>,
+    pub(crate) string_set_heat_map: StringSetHeatMap,
+    pub(crate) string_set_texture: HeatTexture,
+    pub(crate) sweep_range: (f32, f32), // Min and max scale length in mm
+    pub(crate) sweep_rows: usize,
+    pub(crate) scale_sweep_heat_map: ScaleSweepHeatMap,
+    pub(crate) scale_sweep_texture: HeatTexture,
+    pub(crate) blend_pickups: Vec<BlendPickup>,
+    pub(crate) blend_heat_map: BlendHeatMap,
+    pub(crate) blend_texture: HeatTexture,
 }
 
 impl Default for HarmonicApp {
@@ -15,126 +106,1457 @@ impl Default for HarmonicApp {
         let string_length = 650.0; // Typical guitar scale length in mm
         let weights = [0.15, 1.50, 1.50, 1.50, 0.75, 0.75]; // Harmonics 2-7
         let search_limit = (string_length * 0.5) as usize;
-        let optimal_positions =
-            find_optimal_pickup_positions(string_length, &weights, search_limit);
+        let strings = vec![
+            StringConfig {
+                length: string_length,
+                weights,
+                bending_length: 0.0,
+                compensation: NeckGeometry::default().saddle_compensation,
+            };
+            6
+        ];
 
-        Self {
+        let mut app = Self {
             string_length,
             weights,
-            optimal_positions,
+            fret_mode: FretMode::Open,
+            objective: Objective::WeightedSum,
+            stiffness_enabled: false,
+            stiffness: StringStiffness::default(),
+            optimal_positions: OptimalPositions::default(),
+            ideal_positions: OptimalPositions::default(),
+            humbucker_enabled: false,
+            coil_spacing: 17.8, // PAF-style pole piece spacing
+            humbucker_positions: HumbuckerPositions::default(),
+            node_warning_distance: 5.0,
+            node_warning_weight: DEFAULT_NODE_WARNING_WEIGHT,
+            geometry: NeckGeometry::default(),
+            marker_position: None,
+            tuning_frequency: 82.41, // Low E
+            show_response: false,
+            synth: SynthSettings::default(),
+            export_directory: ".".to_owned(),
+            export_status: None,
+            import_path: String::new(),
+            import_status: None,
+            measured_spectrum: None,
             heat_map_resolution: 1000,
             search_limit,
-        }
+            heat_map: HeatMap::default(),
+            colormap: Colormap::Classic,
+            heat_palette: HeatPalette::new(Colormap::Classic),
+            heat_texture: HeatTexture::default(),
+            view: VisualizerView::SingleString,
+            controls_open: true,
+            layout: VisualizerLayout::default(),
+            zoom: ZoomRange::default(),
+            unit: LengthUnit::Millimeters,
+            history: History::default(),
+            strings,
+            string_specs: PRESETS[0].strings.to_vec(),
+            string_optima: Vec::new(),
+            string_set_heat_map: StringSetHeatMap::default(),
+            string_set_texture: HeatTexture::with_options(egui::TextureOptions::NEAREST),
+            sweep_range: (550.0, 900.0),
+            sweep_rows: 120,
+            scale_sweep_heat_map: ScaleSweepHeatMap::default(),
+            scale_sweep_texture: HeatTexture::default(),
+            blend_pickups: vec![
+                BlendPickup {
+                    position: 41.0,
+                    level: 1.0,
+                    reversed: false,
+                },
+                BlendPickup {
+                    position: 160.0,
+                    level: 1.0,
+                    reversed: false,
+                },
+            ],
+            blend_heat_map: BlendHeatMap::default(),
+            blend_texture: HeatTexture::default(),
+        };
+        app.recompute_optimal_positions();
+        app.history.record(app.params(), &app.optimal_positions);
+        app
     }
 }
 
-impl eframe::App for HarmonicApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // Calculate visualizer height first (including separator and spacing)
-            let viz_height = self.calculate_visualizer_height() + 30.0; // +30 for separator and spacing
-
-            let available_height = ui.available_height();
-            let scroll_area_height = available_height - viz_height;
-
-            // Top section: Scrollable controls
-            egui::ScrollArea::vertical()
-                .max_height(scroll_area_height)
-                .show(ui, |ui| {
-                    ui.heading("Harmonic Anti-Node Visualizer");
-                    ui.add_space(10.0);
-
-                    // Controls
-                    ui.label("String Length (mm):");
+impl HarmonicApp {
+    /// Restore the previous session if one was saved, otherwise start from the defaults
+    pub(crate) fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self::default();
+        if let Some(saved) = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<SavedState>(storage, eframe::APP_KEY))
+        {
+            app.restore(saved);
+            app.history = History::default();
+            app.history.record(app.params(), &app.optimal_positions);
+        }
+        app
+    }
+
+    fn saved_state(&self) -> SavedState {
+        SavedState {
+            params: self.params(),
+            view: self.view,
+            layout: self.layout,
+            controls_open: self.controls_open,
+            unit: self.unit,
+            colormap: self.colormap,
+        }
+    }
+
+    fn restore(&mut self, saved: SavedState) {
+        let SavedState {
+            params,
+            view,
+            layout,
+            controls_open,
+            unit,
+            colormap,
+        } = saved;
+        self.view = view;
+        self.layout = layout;
+        self.controls_open = controls_open;
+        self.unit = unit;
+        self.set_colormap(colormap);
+        self.apply_params(params);
+    }
+
+    /// Back to a fresh start. Kept in the history, so it can be undone.
+    fn reset_to_defaults(&mut self) {
+        let defaults = Self::default();
+        self.restore(defaults.saved_state());
+        self.zoom = ZoomRange::default();
+        self.marker_position = None;
+    }
+
+    fn set_colormap(&mut self, colormap: Colormap) {
+        if colormap == self.colormap {
+            return;
+        }
+        self.colormap = colormap;
+        self.heat_palette = HeatPalette::new(colormap);
+
+        // Cached data is still valid but has to be uploaded again in the new colors
+        self.heat_map = HeatMap::default();
+        self.string_set_heat_map = StringSetHeatMap::default();
+        self.scale_sweep_heat_map = ScaleSweepHeatMap::default();
+        self.blend_heat_map = BlendHeatMap::default();
+    }
+
+    pub(crate) fn score_model(&self) -> ScoreModel {
+        ScoreModel {
+            length: self.string_length,
+            weights: self.weights,
+            fret_mode: self.fret_mode,
+            bending_length: if self.stiffness_enabled {
+                self.stiffness.bending_length()
+            } else {
+                0.0
+            },
+            objective: self.objective,
+            compensation: self.geometry.saddle_compensation,
+        }
+    }
+
+    pub(crate) fn params(&self) -> Params {
+        Params {
+            string_length: self.string_length,
+            weights: self.weights,
+            fret_mode: self.fret_mode,
+            objective: self.objective,
+            stiffness_enabled: self.stiffness_enabled,
+            stiffness: self.stiffness,
+            humbucker_enabled: self.humbucker_enabled,
+            coil_spacing: self.coil_spacing,
+            search_limit: self.search_limit,
+            tuning_frequency: self.tuning_frequency,
+            node_warning_distance: self.node_warning_distance,
+            node_warning_weight: self.node_warning_weight,
+            geometry: self.geometry,
+            strings: self.strings.clone(),
+            string_specs: self.string_specs.clone(),
+            sweep_range: self.sweep_range,
+            sweep_rows: self.sweep_rows,
+            blend_pickups: self.blend_pickups.clone(),
+        }
+    }
+
+    fn apply_params(&mut self, params: Params) {
+        let Params {
+            string_length,
+            weights,
+            fret_mode,
+            objective,
+            stiffness_enabled,
+            stiffness,
+            humbucker_enabled,
+            coil_spacing,
+            search_limit,
+            tuning_frequency,
+            node_warning_distance,
+            node_warning_weight,
+            geometry,
+            strings,
+            string_specs,
+            sweep_range,
+            sweep_rows,
+            blend_pickups,
+        } = params;
+        self.string_length = string_length;
+        self.weights = weights;
+        self.fret_mode = fret_mode;
+        self.objective = objective;
+        self.stiffness_enabled = stiffness_enabled;
+        self.stiffness = stiffness;
+        self.humbucker_enabled = humbucker_enabled;
+        self.coil_spacing = coil_spacing;
+        self.search_limit = search_limit;
+        self.tuning_frequency = tuning_frequency;
+        self.node_warning_distance = node_warning_distance;
+        self.node_warning_weight = node_warning_weight;
+        self.geometry = geometry;
+        self.strings = strings;
+        self.string_specs = string_specs;
+        self.sweep_range = sweep_range;
+        self.sweep_rows = sweep_rows;
+        self.blend_pickups = blend_pickups;
+        self.recompute_optimal_positions();
+    }
+
+    /// Ctrl+Z undoes, Ctrl+Shift+Z or Ctrl+Y redoes. Left to text fields while one has focus.
+    fn handle_history_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let (undo, redo) = ctx.input_mut(|i| {
+            let redo = i.consume_shortcut(&egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                egui::Key::Z,
+            )) || i.consume_shortcut(&egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND,
+                egui::Key::Y,
+            ));
+            let undo = i.consume_shortcut(&egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND,
+                egui::Key::Z,
+            ));
+            (undo, redo)
+        });
+
+        let params = if redo {
+            self.history.redo()
+        } else if undo {
+            self.history.undo()
+        } else {
+            None
+        };
+        if let Some(params) = params {
+            self.apply_params(params);
+        }
+    }
+
+    /// Record the current parameters once an edit is finished, so a slider drag or a typed
+    /// number becomes a single history step
+    fn record_history(&mut self, ctx: &egui::Context) {
+        let editing = ctx.input(|i| i.pointer.any_down()) || ctx.wants_keyboard_input();
+        if !editing {
+            self.history.record(self.params(), &self.optimal_positions);
+        }
+    }
+
+    fn history_controls(&mut self, ui: &mut egui::Ui) {
+        let mut target = None;
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.history.can_undo(), egui::Button::new("Undo"))
+                .on_hover_text("Ctrl+Z")
+                .clicked()
+            {
+                target = Some(self.history.current - 1);
+            }
+            if ui
+                .add_enabled(self.history.can_redo(), egui::Button::new("Redo"))
+                .on_hover_text("Ctrl+Shift+Z or Ctrl+Y")
+                .clicked()
+            {
+                target = Some(self.history.current + 1);
+            }
+        });
+
+        egui::ScrollArea::vertical()
+            .id_salt("history_list")
+            .max_height(160.0)
+            .show(ui, |ui| {
+                for (i, entry) in self.history.entries.iter().enumerate().rev() {
+                    let text = format!(
+                        "{}. Bridge {} / Neck {}",
+                        i + 1,
+                        self.unit.format(entry.bridge_position),
+                        self.unit.format(entry.neck_position)
+                    );
                     if ui
-                        .add(egui::Slider::new(&mut self.string_length, 500.0..=1000.0))
-                        .changed()
+                        .selectable_label(i == self.history.current, text)
+                        .clicked()
                     {
-                        self.optimal_positions = find_optimal_pickup_positions(
-                            self.string_length,
-                            &self.weights,
-                            self.search_limit,
-                        );
+                        target = Some(i);
                     }
+                }
+            });
 
-                    ui.label("Search Limit:");
-                    if ui
-                        .add(egui::Slider::new(
-                            &mut self.search_limit,
-                            1..=(self.string_length / 2.0) as usize,
-                        ))
-                        .changed()
-                    {
-                        self.optimal_positions = find_optimal_pickup_positions(
-                            self.string_length,
-                            &self.weights,
-                            self.search_limit,
-                        );
+        if let Some(params) = target.and_then(|i| self.history.jump(i)) {
+            self.apply_params(params);
+        }
+    }
+
+    pub(crate) fn recompute_optimal_positions(&mut self) {
+        let model = self.score_model();
+        self.optimal_positions = find_optimal_pickup_positions(&model, self.search_limit);
+        self.ideal_positions = find_optimal_pickup_positions(
+            &ScoreModel {
+                bending_length: 0.0,
+                ..model
+            },
+            self.search_limit,
+        );
+        if self.humbucker_enabled {
+            self.humbucker_positions =
+                find_optimal_humbucker_positions(&model, self.coil_spacing, self.search_limit);
+        }
+        self.string_optima = self
+            .strings
+            .iter()
+            .map(|string| find_optimal_pickup_positions(&string.model(&model), self.search_limit))
+            .collect();
+    }
+
+    /// Set up scale, strings, weights, search region and blend pickups for an instrument type
+    fn apply_instrument(&mut self, instrument: &Instrument) {
+        let length = instrument.default_scale;
+        self.string_length = length;
+        self.weights = instrument.weights;
+        self.search_limit = instrument.search_limit;
+        // Some instruments vary less than the sweep's minimum span, so widen around the middle
+        let (min, max) = instrument.scale_range;
+        let padding = ((MIN_SWEEP_SPAN - (max - min)) / 2.0).max(0.0);
+        self.sweep_range = (min - padding, max + padding);
+        self.string_specs = instrument.strings.to_vec();
+        self.strings = instrument
+            .strings
+            .iter()
+            .map(|spec| StringConfig {
+                length,
+                weights: spec.suggested_weights(length),
+                bending_length: spec.stiffness(length).bending_length(),
+                compensation: self.geometry.saddle_compensation,
+            })
+            .collect();
+
+        // The single-string view models the lowest string
+        let lowest = instrument.lowest_string();
+        self.stiffness = lowest.stiffness(length);
+        self.tuning_frequency = lowest.frequency();
+        self.synth.pluck_position = length * 0.2;
+        self.marker_position = None;
+        self.zoom = ZoomRange::default();
+        self.recompute_optimal_positions();
+
+        // Blend starts from the optimum positions, with a middle pickup between them if needed
+        let bridge = self.optimal_positions.bridge_position;
+        let neck = self.optimal_positions.neck_position;
+        let positions = match instrument.pickup_count {
+            1 => vec![bridge],
+            2 => vec![bridge, neck],
+            count => (0..count)
+                .map(|i| bridge + (neck - bridge) * i as f32 / (count - 1) as f32)
+                .collect(),
+        };
+        self.blend_pickups = positions
+            .into_iter()
+            .map(|position| BlendPickup {
+                position,
+                level: 1.0,
+                reversed: false,
+            })
+            .collect();
+    }
+
+    fn instrument_selector(&mut self, ui: &mut egui::Ui) {
+        let mut chosen = None;
+        ui.horizontal(|ui| {
+            ui.label("Instrument:");
+            egui::ComboBox::from_id_salt("instrument")
+                .selected_text("Load...")
+                .show_ui(ui, |ui| {
+                    for instrument in &INSTRUMENTS {
+                        let (min, max) = instrument.scale_range;
+                        let response =
+                            ui.selectable_label(false, instrument.name)
+                                .on_hover_text(format!(
+                                    "{} strings, {} to {} scale",
+                                    instrument.strings.len(),
+                                    self.unit.format(min),
+                                    self.unit.format(max)
+                                ));
+                        if response.clicked() {
+                            chosen = Some(instrument);
+                        }
                     }
+                });
+        });
 
-                    ui.add_space(10.0);
-                    ui.separator();
-                    ui.add_space(10.0);
-
-                    // Weight sliders
-                    ui.label("Harmonic Weights:");
-                    let mut weights_changed = false;
-                    for (i, weight) in self.weights.iter_mut().enumerate() {
-                        ui.horizontal(|ui| {
-                            ui.label(format!("Harmonic {}:", i + 2));
-                            if ui.add(egui::Slider::new(weight, 0.0..=2.0)).changed() {
-                                weights_changed = true;
+        if let Some(instrument) = chosen {
+            self.apply_instrument(instrument);
+        }
+    }
+
+    fn string_gauge_editor(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Preset:");
+            egui::ComboBox::from_id_salt("string_set_preset")
+                .selected_text("Load...")
+                .show_ui(ui, |ui| {
+                    for preset in &PRESETS {
+                        if ui.selectable_label(false, preset.name).clicked() {
+                            self.string_specs = preset.strings.to_vec();
+                        }
+                    }
+                });
+        });
+
+        let length = self.string_length;
+        let mut use_for_main = None;
+
+        egui::Grid::new("string_gauge_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for header in [
+                    "String",
+                    "Note",
+                    "Gauge (in)",
+                    "Core (in)",
+                    "Core Material",
+                    "Wrap",
+                    "Tension",
+                    "B",
+                    "",
+                ] {
+                    ui.label(header);
+                }
+                ui.end_row();
+
+                for (i, spec) in self.string_specs.iter_mut().enumerate() {
+                    ui.label(format!("{}", i + 1));
+                    ui.add(
+                        egui::DragValue::new(&mut spec.midi_note)
+                            .range(12..=108)
+                            .custom_formatter(|n, _| note_name(n as u8)),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut spec.gauge_in)
+                            .speed(0.001)
+                            .range(0.005..=0.150)
+                            .max_decimals(3),
+                    );
+                    ui.add_enabled(
+                        spec.wrap.is_some(),
+                        egui::DragValue::new(&mut spec.core_in)
+                            .speed(0.001)
+                            .range(0.005..=spec.gauge_in)
+                            .max_decimals(3),
+                    );
+                    egui::ComboBox::from_id_salt(("core_material", i))
+                        .selected_text(spec.core.label())
+                        .show_ui(ui, |ui| {
+                            for material in Material::ALL {
+                                ui.selectable_value(&mut spec.core, material, material.label());
+                            }
+                        });
+                    egui::ComboBox::from_id_salt(("wrap_material", i))
+                        .selected_text(spec.wrap.map_or("Plain", |wrap| wrap.label()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut spec.wrap, None, "Plain");
+                            for wrap in WrapMaterial::ALL {
+                                ui.selectable_value(&mut spec.wrap, Some(wrap), wrap.label());
                             }
                         });
+
+                    let tension = spec.tension(length);
+                    ui.label(format!("{:.1} N ({:.1} lb)", tension, tension / 4.448));
+                    ui.label(format!(
+                        "{:.1e}",
+                        inharmonicity(spec.stiffness(length).bending_length(), length)
+                    ));
+                    if ui
+                        .small_button("Use")
+                        .on_hover_text(
+                            "Use this string's stiffness and suggested weights for the main view",
+                        )
+                        .clicked()
+                    {
+                        use_for_main = Some(*spec);
                     }
+                    ui.end_row();
+                }
+            });
+
+        ui.horizontal(|ui| {
+            if ui.button("Add String").clicked() && self.string_specs.len() < 12 {
+                let last = *self.string_specs.last().unwrap();
+                self.string_specs.push(last);
+            }
+            if ui.button("Remove String").clicked() && self.string_specs.len() > 1 {
+                self.string_specs.pop();
+            }
+            if ui
+                .button("Apply to String Set")
+                .on_hover_text("Set each string's weights and stiffness from its gauge and tuning")
+                .clicked()
+            {
+                // Compensation is set by hand per string, so keep whatever was there
+                self.strings = self
+                    .string_specs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, spec)| StringConfig {
+                        length,
+                        weights: spec.suggested_weights(length),
+                        bending_length: spec.stiffness(length).bending_length(),
+                        compensation: self
+                            .strings
+                            .get(i)
+                            .map_or(self.geometry.saddle_compensation, |string| {
+                                string.compensation
+                            }),
+                    })
+                    .collect();
+                self.recompute_optimal_positions();
+            }
+        });
+
+        if let Some(spec) = use_for_main {
+            self.weights = spec.suggested_weights(length);
+            self.stiffness = spec.stiffness(length);
+            self.stiffness_enabled = true;
+            self.recompute_optimal_positions();
+        }
+    }
 
-                    if weights_changed {
-                        self.optimal_positions = find_optimal_pickup_positions(
-                            self.string_length,
-                            &self.weights,
-                            self.search_limit,
-                        );
+    fn audio_preview_controls(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("synth_grid").show(ui, |ui| {
+            ui.label("Pluck Position:");
+            ui.add(
+                length_input(&mut self.synth.pluck_position, self.unit)
+                    .range(1.0..=self.string_length - 1.0),
+            );
+            ui.end_row();
+
+            ui.label("Duration (s):");
+            ui.add(egui::Slider::new(&mut self.synth.duration, 0.5..=10.0));
+            ui.end_row();
+
+            ui.label("Partials:");
+            ui.add(egui::Slider::new(&mut self.synth.partials, 1..=100));
+            ui.end_row();
+
+            ui.label("Output Folder:");
+            ui.text_edit_singleline(&mut self.export_directory);
+            ui.end_row();
+        });
+
+        let mut targets = vec![
+            ("bridge", self.optimal_positions.bridge_position),
+            ("neck", self.optimal_positions.neck_position),
+        ];
+        if let Some(marker) = self.marker_position {
+            targets.push(("marker", marker));
+        }
+
+        ui.horizontal(|ui| {
+            for (label, pos) in targets {
+                if ui
+                    .button(format!("Export {} ({})", label, self.unit.format(pos)))
+                    .clicked()
+                {
+                    self.export_preview(label, pos);
+                }
+            }
+        });
+
+        if let Some(status) = &self.export_status {
+            ui.label(status);
+        }
+    }
+
+    fn import_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("WAV recording of a single note, or CSV of harmonic,magnitude rows:");
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.import_path);
+            if ui.button("Import").clicked() {
+                let path = std::path::PathBuf::from(&self.import_path);
+                match import_spectrum(&path, self.tuning_frequency) {
+                    Ok(spectrum) => {
+                        self.weights = spectrum.weights();
+                        self.recompute_optimal_positions();
+                        self.import_status = Some(format!("Imported {}", path.display()));
+                        self.measured_spectrum = Some(spectrum);
+                    }
+                    Err(err) => {
+                        self.import_status =
+                            Some(format!("Failed to import {}: {}", path.display(), err));
                     }
+                }
+            }
+        });
+        ui.weak("WAV analysis uses the open string pitch above as the fundamental.");
 
-                    ui.add_space(20.0);
-                    ui.separator();
-                    ui.add_space(10.0);
-
-                    // Results
-                    ui.horizontal(|ui| {
-                        ui.label("Bridge Pickup:");
-                        ui.colored_label(
-                            Color32::LIGHT_BLUE,
-                            format!(
-                                "{:.2} mm from bridge",
-                                self.optimal_positions.bridge_position
-                            ),
-                        );
-                        ui.label(format!(
-                            "({:.1}%)",
-                            (self.optimal_positions.bridge_position / self.string_length) * 100.0
-                        ));
+        if let Some(status) = &self.import_status {
+            ui.label(status);
+        }
+
+        if let Some(spectrum) = &self.measured_spectrum {
+            let reference = spectrum.amplitudes.iter().cloned().fold(0.0_f32, f32::max);
+            ui.horizontal_wrapped(|ui| {
+                for (i, &amplitude) in spectrum.amplitudes.iter().enumerate() {
+                    let db = if reference > 0.0 && amplitude > 0.0 {
+                        20.0 * (amplitude / reference).log10()
+                    } else {
+                        f32::NEG_INFINITY
+                    };
+                    ui.label(format!("H{}: {:.1} dB", i + 1, db));
+                }
+            });
+        }
+    }
+
+    fn export_preview(&mut self, label: &str, pos: f32) {
+        let samples = render_pluck(&self.score_model(), self.tuning_frequency, pos, &self.synth);
+        let path = std::path::Path::new(&self.export_directory)
+            .join(format!("bpcalc_{}_{:.1}mm.wav", label, pos));
+
+        self.export_status = Some(match write_wav(&path, &samples, self.synth.sample_rate) {
+            Ok(()) => format!("Wrote {}", path.display()),
+            Err(err) => format!("Failed to write {}: {}", path.display(), err),
+        });
+    }
+
+    /// Recompute whichever heat maps are stale and re-upload their textures
+    fn refresh_heat_maps(&mut self, ctx: &egui::Context) {
+        if self.heat_map.update(
+            &self.score_model(),
+            self.zoom.range_mm(self.string_length),
+            self.heat_map_resolution,
+        ) {
+            self.heat_texture.upload(
+                ctx,
+                "heat_map",
+                &self.heat_map.values,
+                [self.heat_map.values.len(), 1],
+                self.heat_map.range,
+                &self.heat_palette,
+            );
+        }
+        let string_models: Vec<ScoreModel> = if self.view == VisualizerView::StringSet {
+            let model = self.score_model();
+            self.strings.iter().map(|s| s.model(&model)).collect()
+        } else {
+            Vec::new()
+        };
+        if self.view == VisualizerView::StringSet
+            && self
+                .string_set_heat_map
+                .update(&string_models, self.heat_map_resolution)
+        {
+            self.string_set_texture.upload(
+                ctx,
+                "string_set_heat_map",
+                &self.string_set_heat_map.values,
+                self.string_set_heat_map.size,
+                self.string_set_heat_map.range,
+                &self.heat_palette,
+            );
+        }
+        if self.view == VisualizerView::ScaleSweep
+            && self.scale_sweep_heat_map.update(
+                &self.score_model(),
+                self.sweep_range,
+                self.sweep_rows,
+                self.heat_map_resolution,
+                self.search_limit,
+            )
+        {
+            self.scale_sweep_texture.upload(
+                ctx,
+                "scale_sweep_heat_map",
+                &self.scale_sweep_heat_map.values,
+                self.scale_sweep_heat_map.size,
+                self.scale_sweep_heat_map.range,
+                &self.heat_palette,
+            );
+        }
+        if self.view == VisualizerView::Blend
+            && self.blend_heat_map.update(
+                &self.score_model(),
+                &self.blend_pickups,
+                self.heat_map_resolution,
+            )
+        {
+            self.blend_texture.upload(
+                ctx,
+                "blend_heat_map",
+                &self.blend_heat_map.values,
+                [self.blend_heat_map.values.len(), 1],
+                self.blend_heat_map.range,
+                &self.heat_palette,
+            );
+        }
+    }
+
+    fn blend_editor(&mut self, ui: &mut egui::Ui) {
+        ui.label("The first pickup is swept along the string to draw the blend heat map.");
+
+        let mut removed = None;
+        egui::Grid::new("blend_grid").striped(true).show(ui, |ui| {
+            ui.label("Pickup");
+            ui.label("Position");
+            ui.label("Level");
+            ui.label("Phase");
+            ui.end_row();
+
+            for (i, pickup) in self.blend_pickups.iter_mut().enumerate() {
+                ui.label(format!("{}", i + 1));
+                ui.add(
+                    length_input(&mut pickup.position, self.unit).range(0.0..=self.string_length),
+                );
+                ui.add(egui::Slider::new(&mut pickup.level, 0.0..=1.0));
+                ui.checkbox(&mut pickup.reversed, "Reversed");
+                if ui.small_button("Remove").clicked() {
+                    removed = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(i) = removed
+            && self.blend_pickups.len() > 1
+        {
+            self.blend_pickups.remove(i);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Add Pickup").clicked() && self.blend_pickups.len() < 6 {
+                self.blend_pickups.push(BlendPickup {
+                    position: self.string_length * 0.1,
+                    level: 1.0,
+                    reversed: false,
+                });
+            }
+            if ui.button("Use Optimum Positions").clicked() {
+                let positions = [
+                    self.optimal_positions.bridge_position,
+                    self.optimal_positions.neck_position,
+                ];
+                for (pickup, position) in self.blend_pickups.iter_mut().zip(positions) {
+                    pickup.position = position;
+                }
+            }
+        });
+    }
+
+    fn humbucker_controls(&mut self, ui: &mut egui::Ui) {
+        let previous = (self.humbucker_enabled, self.coil_spacing);
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.humbucker_enabled, "Humbuckers");
+            ui.label("Coil Spacing:");
+            ui.add(
+                length_input(&mut self.coil_spacing, self.unit)
+                    .speed(0.1)
+                    .range(5.0..=40.0),
+            );
+        });
+
+        if (self.humbucker_enabled, self.coil_spacing) != previous {
+            self.recompute_optimal_positions();
+        }
+    }
+
+    /// Flag recommended positions sitting close to a node of a harmonic weighted at least
+    /// `node_warning_weight`
+    fn node_warnings(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Warn within");
+            ui.add(
+                length_input(&mut self.node_warning_distance, self.unit)
+                    .speed(0.1)
+                    .range(0.0..=50.0),
+            );
+            ui.label("of a node");
+        });
+        ui.horizontal(|ui| {
+            ui.label("for harmonics weighted at least");
+            ui.add(
+                egui::DragValue::new(&mut self.node_warning_weight)
+                    .speed(0.01)
+                    .range(0.0..=2.0),
+            );
+        });
+
+        let mut positions = vec![
+            ("Bridge pickup", self.optimal_positions.bridge_position),
+            ("Neck pickup", self.optimal_positions.neck_position),
+        ];
+        if self.humbucker_enabled {
+            for (label, placement) in [
+                ("Bridge humbucker", self.humbucker_positions.bridge),
+                ("Neck humbucker", self.humbucker_positions.neck),
+            ] {
+                positions.extend(placement.coils.iter().map(|&coil| (label, coil)));
+            }
+        }
+
+        let model = self.score_model();
+        for (label, pos) in positions {
+            for (harmonic, gap) in nearby_nodes(
+                &model,
+                pos,
+                self.node_warning_distance,
+                self.node_warning_weight,
+            ) {
+                ui.colored_label(
+                    Color32::from_rgb(255, 170, 80),
+                    format!(
+                        "⚠ {} at {} is {} from a node of H{}",
+                        label,
+                        self.unit.format(pos),
+                        self.unit.format(gap),
+                        harmonic
+                    ),
+                );
+            }
+        }
+    }
+
+    fn geometry_controls(&mut self, ui: &mut egui::Ui) {
+        let previous = self.geometry.saddle_compensation;
+        egui::Grid::new("geometry_grid").show(ui, |ui| {
+            ui.label("Frets:");
+            ui.add(egui::DragValue::new(&mut self.geometry.fret_count).range(1..=36));
+            ui.end_row();
+
+            ui.label("Neck pocket past last fret:");
+            ui.add(
+                length_input(&mut self.geometry.pocket_overhang, self.unit)
+                    .speed(0.1)
+                    .range(0.0..=100.0),
+            );
+            ui.end_row();
+
+            ui.label("Saddle compensation:");
+            ui.add(
+                length_input(&mut self.geometry.saddle_compensation, self.unit)
+                    .speed(0.05)
+                    .range(0.0..=10.0),
+            )
+            .on_hover_text(
+                "Saddle setback behind the bridge line. Harmonics are laid out on the \
+                 compensated length; positions are still measured from the bridge line.",
+            );
+            ui.end_row();
+        });
+
+        // The main string vibrates from the saddle, so this moves the optimum
+        if self.geometry.saddle_compensation != previous {
+            self.recompute_optimal_positions();
+        }
+    }
+
+    /// Named positions worth measuring, in mm from the bridge
+    fn reported_positions(&self) -> Vec<(&'static str, f32)> {
+        let mut positions = vec![
+            ("Bridge pickup", self.optimal_positions.bridge_position),
+            ("Neck pickup", self.optimal_positions.neck_position),
+        ];
+        if self.humbucker_enabled {
+            positions.push(("Bridge humbucker", self.humbucker_positions.bridge.center));
+            positions.push(("Neck humbucker", self.humbucker_positions.neck.center));
+        }
+        if let Some(marker) = self.marker_position {
+            positions.push(("Marker", marker));
+        }
+        positions
+    }
+
+    /// Every reported position measured from each reference point, plus the pickup gap.
+    /// Offsets are signed, positive towards the nut, so the side of the reference is kept.
+    /// The copy button puts the same table on the clipboard as tab-separated text.
+    fn position_table(&self, ui: &mut egui::Ui) {
+        let positions = self.reported_positions();
+        let unit = self.unit.label();
+
+        let mut header = vec!["+ towards nut".to_owned()];
+        header.extend(
+            ReferenceFrame::ALL
+                .iter()
+                .map(|frame| format!("{} ({})", frame.label(), unit)),
+        );
+        header.push("% of scale".to_owned());
+
+        let mut rows = vec![header];
+        for &(label, pos) in &positions {
+            let mut row = vec![label.to_owned()];
+            row.extend(ReferenceFrame::ALL.iter().map(|frame| {
+                self.unit
+                    .format_number(frame.offset(pos, self.string_length, &self.geometry))
+            }));
+            row.push(format!("{:.1}", pos / self.string_length * 100.0));
+            rows.push(row);
+        }
+
+        egui::ScrollArea::horizontal()
+            .id_salt("position_table")
+            .show(ui, |ui| {
+                egui::Grid::new("position_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (i, row) in rows.iter().enumerate() {
+                            for (j, cell) in row.iter().enumerate() {
+                                if i == 0 || j == 0 {
+                                    ui.strong(cell);
+                                } else if j == 1 {
+                                    ui.colored_label(Color32::LIGHT_BLUE, cell);
+                                } else {
+                                    ui.label(cell);
+                                }
+                            }
+                            ui.end_row();
+                        }
                     });
+            });
+
+        let gap = self.optimal_positions.neck_position - self.optimal_positions.bridge_position;
+        ui.horizontal(|ui| {
+            ui.label("Gap between pickups:");
+            ui.colored_label(Color32::LIGHT_BLUE, self.unit.format(gap));
+            if self.humbucker_enabled {
+                let humbucker_gap =
+                    self.humbucker_positions.neck.center - self.humbucker_positions.bridge.center;
+                ui.label(format!("(humbuckers {})", self.unit.format(humbucker_gap)));
+            }
+        });
+
+        if ui.button("Copy Table").clicked() {
+            let mut text: Vec<String> = rows.iter().map(|row| row.join("\t")).collect();
+            text.push(format!(
+                "Gap between pickups ({})\t{}",
+                unit,
+                self.unit.format_number(gap)
+            ));
+            ui.ctx().copy_text(text.join("\n"));
+        }
+    }
 
-                    ui.horizontal(|ui| {
-                        ui.label("Neck Pickup:");
-                        ui.colored_label(
-                            Color32::LIGHT_BLUE,
-                            format!("{:.2} mm from bridge", self.optimal_positions.neck_position),
-                        );
-                        ui.label(format!(
-                            "({:.1}%)",
-                            (self.optimal_positions.neck_position / self.string_length) * 100.0
-                        ));
+    fn humbucker_results(&self, ui: &mut egui::Ui) {
+        for (label, placement) in [
+            ("Bridge Humbucker:", self.humbucker_positions.bridge),
+            ("Neck Humbucker:", self.humbucker_positions.neck),
+        ] {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                ui.colored_label(
+                    Color32::LIGHT_BLUE,
+                    format!("center {}", self.unit.format(placement.center)),
+                );
+                ui.label(format!(
+                    "(coils at {} / {})",
+                    self.unit.format(placement.coils[0]),
+                    self.unit.format(placement.coils[1])
+                ));
+            });
+        }
+
+        // Harmonics noticeably thinned out by the spacing itself
+        let cancelled: Vec<String> =
+            coil_spacing_attenuation(self.string_length, self.coil_spacing, 20)
+                .into_iter()
+                .enumerate()
+                .filter(|&(_, db)| db < -3.0)
+                .map(|(i, db)| format!("H{} {:.1} dB", i + 1, db))
+                .collect();
+        if !cancelled.is_empty() {
+            ui.label(format!("Coil spacing attenuates: {}", cancelled.join(", ")));
+        }
+    }
+
+    fn stiffness_controls(&mut self, ui: &mut egui::Ui) {
+        let previous = (self.stiffness_enabled, self.stiffness);
+
+        ui.checkbox(&mut self.stiffness_enabled, "Model string stiffness");
+        ui.add_enabled_ui(self.stiffness_enabled, |ui| {
+            egui::Grid::new("stiffness_grid").show(ui, |ui| {
+                ui.label("Core Material:");
+                egui::ComboBox::from_id_salt("core_material")
+                    .selected_text(self.stiffness.material.label())
+                    .show_ui(ui, |ui| {
+                        for material in Material::ALL {
+                            ui.selectable_value(
+                                &mut self.stiffness.material,
+                                material,
+                                material.label(),
+                            );
+                        }
                     });
+                ui.end_row();
+
+                ui.label("Gauge (in):");
+                ui.add(
+                    egui::DragValue::new(&mut self.stiffness.gauge_in)
+                        .speed(0.001)
+                        .range(0.005..=0.150)
+                        .max_decimals(3),
+                );
+                ui.end_row();
+
+                ui.label("Core Diameter (in):");
+                ui.add(
+                    egui::DragValue::new(&mut self.stiffness.core_in)
+                        .speed(0.001)
+                        .range(0.005..=0.150)
+                        .max_decimals(3),
+                );
+                ui.end_row();
+
+                ui.label("Tension (N):");
+                ui.add(
+                    egui::DragValue::new(&mut self.stiffness.tension_n)
+                        .speed(0.5)
+                        .range(1.0..=400.0),
+                );
+                ui.end_row();
+            });
+
+            let bending_length = self.stiffness.bending_length();
+            ui.label(format!(
+                "Inharmonicity B = {:.2e} (bending length {})",
+                inharmonicity(bending_length, self.string_length),
+                self.unit.format(bending_length)
+            ));
+            if self.stiffness_enabled {
+                ui.label(format!(
+                    "Optimum shift vs. ideal string: bridge {}, neck {}",
+                    self.unit.format_signed(
+                        self.optimal_positions.bridge_position
+                            - self.ideal_positions.bridge_position
+                    ),
+                    self.unit.format_signed(
+                        self.optimal_positions.neck_position - self.ideal_positions.neck_position
+                    ),
+                ));
+            }
+        });
+
+        if (self.stiffness_enabled, self.stiffness) != previous {
+            self.recompute_optimal_positions();
+        }
+    }
+
+    fn fret_mode_controls(&mut self, ui: &mut egui::Ui) {
+        let mut fret = match self.fret_mode {
+            FretMode::Open => 12,
+            FretMode::Fretted(fret) | FretMode::AverageUpTo(fret) => fret,
+        };
+        let previous = self.fret_mode;
+
+        ui.horizontal(|ui| {
+            ui.label("Note:");
+            ui.radio_value(&mut self.fret_mode, FretMode::Open, "Open");
+            ui.radio_value(&mut self.fret_mode, FretMode::Fretted(fret), "Fretted");
+            ui.radio_value(
+                &mut self.fret_mode,
+                FretMode::AverageUpTo(fret),
+                "Average Frets 0..N",
+            );
+        });
+
+        if self.fret_mode != FretMode::Open {
+            ui.horizontal(|ui| {
+                ui.label("Fret:");
+                if ui.add(egui::Slider::new(&mut fret, 1..=24)).changed() {
+                    self.fret_mode = match self.fret_mode {
+                        FretMode::AverageUpTo(_) => FretMode::AverageUpTo(fret),
+                        _ => FretMode::Fretted(fret),
+                    };
+                }
+            });
+        }
+
+        if self.fret_mode != previous {
+            self.recompute_optimal_positions();
+        }
+    }
+
+    fn objective_controls(&mut self, ui: &mut egui::Ui) {
+        let previous = self.objective;
+        let (mut target, mut suppress) = match self.objective {
+            Objective::TargetSuppress { target, suppress } => (target, suppress),
+            _ => (3, 2),
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Objective:");
+            egui::ComboBox::from_id_salt("objective")
+                .selected_text(self.objective.label())
+                .show_ui(ui, |ui| {
+                    for objective in [
+                        Objective::WeightedSum,
+                        Objective::TargetSuppress { target, suppress },
+                        Objective::MaxMin,
+                        Objective::MatchShape,
+                        Objective::AvoidNodes,
+                    ] {
+                        ui.selectable_value(&mut self.objective, objective, objective.label());
+                    }
                 });
+        });
+
+        if matches!(self.objective, Objective::TargetSuppress { .. }) {
+            ui.horizontal(|ui| {
+                ui.label("Favor H:");
+                let target_changed = ui
+                    .add(egui::DragValue::new(&mut target).range(2..=7))
+                    .changed();
+                ui.label("Suppress H:");
+                let previous_suppress = suppress;
+                ui.add(egui::DragValue::new(&mut suppress).range(2..=7));
+
+                // The two must differ: a dragged suppress value skips over the target, and a
+                // target moved onto suppress pushes it to a neighbor
+                if target == suppress {
+                    let step_up = if target_changed {
+                        target < 7
+                    } else {
+                        (suppress > previous_suppress && suppress < 7) || suppress == 2
+                    };
+                    suppress = if step_up { suppress + 1 } else { suppress - 1 };
+                }
+            });
+            self.objective = Objective::TargetSuppress { target, suppress };
+        }
+
+        if self.objective != previous {
+            self.recompute_optimal_positions();
+        }
+    }
+
+    fn strings_editor(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+
+        egui::Grid::new("strings_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("String");
+                ui.label("Length");
+                ui.label("Saddle")
+                    .on_hover_text("Compensation: how far the saddle sits behind the bridge line");
+                for harmonic in 2..=7 {
+                    ui.label(format!("H{}", harmonic));
+                }
+                ui.end_row();
+
+                for (i, string) in self.strings.iter_mut().enumerate() {
+                    ui.label(format!("{}", i + 1));
+                    changed |= ui
+                        .add(
+                            length_input(&mut string.length, self.unit)
+                                .range(MIN_SCALE_LENGTH..=MAX_SCALE_LENGTH),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            length_input(&mut string.compensation, self.unit)
+                                .speed(0.05)
+                                .range(0.0..=10.0),
+                        )
+                        .changed();
+                    for weight in string.weights.iter_mut() {
+                        changed |= ui
+                            .add(egui::DragValue::new(weight).speed(0.01).range(-2.0..=2.0))
+                            .changed();
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.horizontal(|ui| {
+            if ui.button("Add String").clicked() && self.strings.len() < 12 {
+                let last = *self.strings.last().unwrap();
+                self.strings.push(last);
+                changed = true;
+            }
+            if ui.button("Remove String").clicked() && self.strings.len() > 1 {
+                self.strings.pop();
+                changed = true;
+            }
+            if ui.button("Copy Main Settings").clicked() {
+                for string in self.strings.iter_mut() {
+                    string.length = self.string_length;
+                    string.weights = self.weights;
+                    string.compensation = self.geometry.saddle_compensation;
+                }
+                changed = true;
+            }
+        });
+
+        if changed {
+            self.recompute_optimal_positions();
+        }
+    }
+
+    fn controls(&mut self, ui: &mut egui::Ui) {
+        ui.add_space(6.0);
+
+        if self.view == VisualizerView::StringSet {
+            egui::CollapsingHeader::new("Strings")
+                .default_open(true)
+                .show(ui, |ui| self.strings_editor(ui));
+            ui.add_space(10.0);
+        }
+
+        if self.view == VisualizerView::ScaleSweep {
+            ui.horizontal(|ui| {
+                let (min, max) = &mut self.sweep_range;
+                ui.label("Sweep Scale Lengths:");
+                ui.add(
+                    length_input(min, self.unit)
+                        .speed(1.0)
+                        .range(MIN_SCALE_LENGTH..=*max - MIN_SWEEP_SPAN),
+                );
+                ui.label("to");
+                ui.add(
+                    length_input(max, self.unit)
+                        .speed(1.0)
+                        .range(*min + MIN_SWEEP_SPAN..=MAX_SCALE_LENGTH),
+                );
+                ui.label("Rows:");
+                ui.add(egui::Slider::new(&mut self.sweep_rows, 10..=400));
+            });
+            ui.add_space(10.0);
+        }
+
+        if self.view == VisualizerView::Blend {
+            egui::CollapsingHeader::new("Pickup Blend")
+                .default_open(true)
+                .show(ui, |ui| self.blend_editor(ui));
+            ui.add_space(10.0);
+        }
+
+        // Controls
+        self.instrument_selector(ui);
+        ui.add_space(6.0);
+
+        // Slider for quick changes, text entry in any unit for exact ones
+        ui.label("String Length:");
+        let length_changed = ui
+            .horizontal(|ui| {
+                let slider = ui.add(
+                    egui::Slider::new(&mut self.string_length, MIN_SCALE_LENGTH..=MAX_SCALE_LENGTH)
+                        .show_value(false),
+                );
+                let input = ui.add(
+                    length_input(&mut self.string_length, self.unit)
+                        .range(MIN_SCALE_LENGTH..=MAX_SCALE_LENGTH),
+                );
+                slider.changed() || input.changed()
+            })
+            .inner;
+        if length_changed {
+            self.recompute_optimal_positions();
+        }
+
+        // Searched in thousandths of the scale, shown as a percentage
+        ui.label("Search Limit:");
+        if ui
+            .add(
+                egui::Slider::new(&mut self.search_limit, 1..=500)
+                    .custom_formatter(|value, _| format!("{:.1}% of scale", value / 10.0))
+                    .custom_parser(|text| {
+                        let text = text.trim().trim_end_matches("of scale").trim();
+                        let percent: f64 = text.trim_end_matches('%').trim().parse().ok()?;
+                        Some(percent * 10.0)
+                    }),
+            )
+            .changed()
+        {
+            self.recompute_optimal_positions();
+        }
+
+        self.fret_mode_controls(ui);
+        self.objective_controls(ui);
+        self.humbucker_controls(ui);
+
+        egui::CollapsingHeader::new("String Stiffness").show(ui, |ui| self.stiffness_controls(ui));
+
+        egui::CollapsingHeader::new("String Gauges & Tension")
+            .show(ui, |ui| self.string_gauge_editor(ui));
+
+        ui.add_space(10.0);
+        ui.separator();
+        ui.add_space(10.0);
+
+        // Weight sliders
+        ui.label("Harmonic Weights:");
+        let mut weights_changed = false;
+        for (i, weight) in self.weights.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("Harmonic {}:", i + 2));
+                // Typed values may go past the slider's range
+                let slider =
+                    egui::Slider::new(weight, -2.0..=2.0).clamping(egui::SliderClamping::Never);
+                if ui.add(slider).changed() {
+                    weights_changed = true;
+                }
+            });
+        }
+
+        if weights_changed {
+            self.recompute_optimal_positions();
+        }
+
+        ui.add_space(20.0);
+        ui.separator();
+        ui.add_space(10.0);
+
+        egui::CollapsingHeader::new("Neck Geometry").show(ui, |ui| self.geometry_controls(ui));
+        ui.add_space(10.0);
+
+        // Results
+        self.position_table(ui);
+
+        if self.humbucker_enabled {
+            self.humbucker_results(ui);
+        }
+
+        self.node_warnings(ui);
+
+        if let Some(marker) = self.marker_position {
+            ui.horizontal(|ui| {
+                ui.label("Marker:");
+                ui.colored_label(
+                    Color32::LIGHT_BLUE,
+                    format!("{} from bridge", self.unit.format(marker)),
+                );
+                ui.label(format!("({:.1}%)", (marker / self.string_length) * 100.0));
+                if ui.small_button("Clear").clicked() {
+                    self.marker_position = None;
+                }
+            });
+        } else {
+            ui.weak("Click the string diagram to place a marker.");
+        }
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_response, "Show frequency response");
+            ui.label("Open string pitch (Hz):");
+            ui.add(
+                egui::DragValue::new(&mut self.tuning_frequency)
+                    .speed(0.1)
+                    .range(20.0..=2000.0),
+            );
+        });
+
+        egui::CollapsingHeader::new("Audio Preview").show(ui, |ui| self.audio_preview_controls(ui));
+
+        egui::CollapsingHeader::new("Import Measured Spectrum")
+            .show(ui, |ui| self.import_controls(ui));
+
+        egui::CollapsingHeader::new("History").show(ui, |ui| self.history_controls(ui));
+    }
+}
+
+impl eframe::App for HarmonicApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, &self.saved_state());
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.handle_history_shortcuts(ctx);
+        self.refresh_heat_maps(ctx);
+
+        egui::TopBottomPanel::top("toolbar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.toggle_value(&mut self.controls_open, "☰ Controls")
+                    .on_hover_text("Show or hide the controls panel");
+                ui.heading("Harmonic Anti-Node Visualizer");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui
+                        .button("Reset to Defaults")
+                        .on_hover_text("Restore default parameters and layout (undoable)")
+                        .clicked()
+                    {
+                        self.reset_to_defaults();
+                    }
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.label("View:");
+                ui.selectable_value(
+                    &mut self.view,
+                    VisualizerView::SingleString,
+                    "Single String",
+                );
+                ui.selectable_value(&mut self.view, VisualizerView::StringSet, "String Set");
+                ui.selectable_value(&mut self.view, VisualizerView::ScaleSweep, "Scale Sweep");
+                ui.selectable_value(&mut self.view, VisualizerView::Blend, "Blend");
 
-            // Bottom section: Visualization anchored to bottom
-            ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
-                self.draw_visualization(ui);
                 ui.separator();
-                ui.add_space(10.0);
+                ui.label("Units:");
+                for unit in LengthUnit::ALL {
+                    ui.selectable_value(&mut self.unit, unit, unit.label());
+                }
+
+                ui.separator();
+                let mut colormap = self.colormap;
+                egui::ComboBox::from_id_salt("colormap")
+                    .selected_text(colormap.label())
+                    .show_ui(ui, |ui| {
+                        for option in Colormap::ALL {
+                            ui.selectable_value(&mut colormap, option, option.label());
+                        }
+                    });
+                self.set_colormap(colormap);
+
+                if self.view == VisualizerView::SingleString {
+                    ui.separator();
+                    if ui
+                        .add_enabled(self.zoom.is_zoomed(), egui::Button::new("Reset Zoom"))
+                        .on_hover_text("Scroll over the string to zoom, drag to pan")
+                        .clicked()
+                    {
+                        self.zoom = ZoomRange::default();
+                    }
+                }
             });
         });
+
+        // Controls live in a resizable side panel that can be collapsed to give the
+        // visualizer the whole window
+        egui::SidePanel::left("controls")
+            .resizable(true)
+            .default_width(CONTROLS_DEFAULT_WIDTH)
+            .width_range(CONTROLS_MIN_WIDTH..=CONTROLS_MAX_WIDTH)
+            .show_animated(ctx, self.controls_open, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| self.controls(ui));
+            });
+
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| self.draw_visualization(ui));
+
+        self.record_history(ctx);
     }
 }
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::calculation::ScoreModel;
+use crate::response::mode_shape;
+
+/// One pickup in a blend, e.g. the bridge coil of a neck/bridge selector position
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BlendPickup {
+    pub(crate) position: f32, // mm from bridge
+    pub(crate) level: f32,    // Mix level, 0..1
+    pub(crate) reversed: bool,
+}
+
+impl BlendPickup {
+    fn gain(&self) -> f32 {
+        if self.reversed {
+            -self.level
+        } else {
+            self.level
+        }
+    }
+}
+
+/// Signed amplitude of each partial 1..=count after summing all pickups.
+/// Phase matters here: two pickups on opposite sides of a node cancel when wired in phase
+/// and reinforce when one is reversed.
+pub(crate) fn blend_response(model: &ScoreModel, pickups: &[BlendPickup], count: u8) -> Vec<f32> {
+    (1..=count)
+        .map(|harmonic| {
+            pickups
+                .iter()
+                .map(|pickup| pickup.gain() * mode_shape(model, harmonic, pickup.position))
+                .sum()
+        })
+        .collect()
+}
+
+/// The model's objective applied to the blended magnitudes of harmonics 2-7, so a single
+/// pickup at full level scores the same as it would on its own
+pub(crate) fn blend_score(model: &ScoreModel, pickups: &[BlendPickup]) -> f32 {
+    let mut responses = [0.0; 6];
+    for (response, amplitude) in responses
+        .iter_mut()
+        .zip(blend_response(model, pickups, 7).into_iter().skip(1))
+    {
+        *response = amplitude.abs();
+    }
+    model.objective.evaluate(&responses, &model.weights)
+}
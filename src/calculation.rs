@@ -1,8 +1,239 @@
+use serde::{Deserialize, Serialize};
+
+use crate::stiffness::inharmonicity;
+
+/// One string of a set, with its own scale length, harmonic weights and stiffness
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct StringConfig {
+    pub(crate) length: f32,
+    pub(crate) weights: [f32; 6],
+    pub(crate) bending_length: f32,
+    /// Saddle setback behind the bridge line for this string, in mm
+    #[serde(default)]
+    pub(crate) compensation: f32,
+}
+
+impl StringConfig {
+    /// This string's model, taking everything that isn't per-string from `base`
+    pub(crate) fn model(&self, base: &ScoreModel) -> ScoreModel {
+        ScoreModel {
+            length: self.length,
+            weights: self.weights,
+            bending_length: self.bending_length,
+            compensation: self.compensation,
+            ..*base
+        }
+    }
+}
+
+/// What makes one pickup position better than another, given each harmonic's response
+/// (0 at a node, 1 at an anti-node) and the harmonic weights
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Objective {
+    /// Weighted sum of the responses
+    WeightedSum,
+    /// Favor one harmonic while pushing another toward a node. With both set to the same
+    /// harmonic there is nothing to suppress, so it is simply favored.
+    TargetSuppress { target: u8, suppress: u8 },
+    /// The weakest response among the weighted harmonics, so none is left out
+    MaxMin,
+    /// How closely the responses follow the weights read as a target spectrum. Harmonics
+    /// weighted at or below zero are targeted at a node, so all-negative weights still steer.
+    MatchShape,
+    /// Penalty that rises steeply as a weighted harmonic approaches one of its nodes
+    AvoidNodes,
+}
+
+impl Objective {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Objective::WeightedSum => "Maximize weighted sum",
+            Objective::TargetSuppress { .. } => "Target / suppress harmonic",
+            Objective::MaxMin => "Maximize weakest harmonic",
+            Objective::MatchShape => "Match weight shape",
+            Objective::AvoidNodes => "Avoid nodes",
+        }
+    }
+
+    /// `responses` and `weights` are for harmonics 2-7
+    pub(crate) fn evaluate(&self, responses: &[f32; 6], weights: &[f32; 6]) -> f32 {
+        let weighted = responses.iter().zip(weights.iter());
+
+        match *self {
+            Objective::WeightedSum => weighted.map(|(r, w)| w * r).sum(),
+            Objective::TargetSuppress { target, suppress } => {
+                let response = |harmonic: u8| {
+                    responses
+                        .get((harmonic as usize).wrapping_sub(2))
+                        .copied()
+                        .unwrap_or(0.0)
+                };
+                if target == suppress {
+                    response(target)
+                } else {
+                    response(target) - response(suppress)
+                }
+            }
+            Objective::MaxMin => weighted
+                .filter(|&(_, &w)| w > 0.0)
+                .map(|(&r, _)| r)
+                .fold(None, |min: Option<f32>, r| {
+                    Some(min.map_or(r, |m| m.min(r)))
+                })
+                .unwrap_or(0.0),
+            Objective::MatchShape => {
+                let max_weight = weights.iter().cloned().fold(0.0_f32, f32::max);
+                let target = |w: f32| {
+                    if max_weight > 0.0 {
+                        (w / max_weight).max(0.0)
+                    } else {
+                        0.0
+                    }
+                };
+                let squared_error: f32 = weighted.map(|(r, &w)| (r - target(w)).powi(2)).sum();
+                -(squared_error / responses.len() as f32).sqrt()
+            }
+            Objective::AvoidNodes => -weighted.map(|(r, w)| w * (1.0 - r).powi(4)).sum::<f32>(),
+        }
+    }
+}
+
+/// Which note the string is assumed to be playing when scoring a fixed pickup
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) enum FretMode {
+    Open,
+    Fretted(u8),
+    /// Mean of the scores for frets 0..=N
+    AverageUpTo(u8),
+}
+
+/// Everything the score at a bridge-referenced position depends on. Positions are measured
+/// from the nominal bridge line, but the string vibrates from the saddle `compensation` behind it.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct ScoreModel {
+    pub(crate) length: f32,
+    pub(crate) weights: [f32; 6],
+    pub(crate) fret_mode: FretMode,
+    /// sqrt(EI/T) in mm; zero for an ideal, perfectly flexible string
+    pub(crate) bending_length: f32,
+    pub(crate) objective: Objective,
+    /// How far the saddle contact point sits behind the bridge line, in mm
+    pub(crate) compensation: f32,
+}
+
+impl ScoreModel {
+    /// Score of a pickup at `pos`, or None past `scored_length` where a scored note has no
+    /// signal. Scoring that as zero would rank it above every position that scores below zero.
+    pub(crate) fn score(&self, pos: f32) -> Option<f32> {
+        if pos > self.scored_length() {
+            return None;
+        }
+
+        Some(match self.fret_mode {
+            FretMode::Open => self.objective_score(self.length + self.compensation, pos),
+            FretMode::Fretted(fret) => self.fretted_score(fret, pos),
+            FretMode::AverageUpTo(max_fret) => {
+                let total: f32 = (0..=max_fret)
+                    .map(|fret| self.fretted_score(fret, pos))
+                    .sum();
+                total / (max_fret as f32 + 1.0)
+            }
+        })
+    }
+
+    /// Farthest point from the bridge that still hears every note being scored: the nut when
+    /// open, otherwise the stopping point of the highest fret
+    pub(crate) fn scored_length(&self) -> f32 {
+        match self.fret_mode {
+            FretMode::Open => self.length,
+            FretMode::Fretted(fret) | FretMode::AverageUpTo(fret) => {
+                fretted_length(self.length, fret)
+            }
+        }
+    }
+
+    /// Length from the saddle to where the note is stopped. Averaged modes use the open string.
+    pub(crate) fn vibrating_length(&self) -> f32 {
+        let nominal = match self.fret_mode {
+            FretMode::Fretted(fret) => fretted_length(self.length, fret),
+            FretMode::Open | FretMode::AverageUpTo(_) => self.length,
+        };
+        nominal + self.compensation
+    }
+
+    /// Anti-nodes of `harmonic` for the note being played, in mm from the bridge line
+    pub(crate) fn anti_nodes(&self, harmonic: u8) -> Vec<f32> {
+        get_anti_nodes_for_harmonic(self.vibrating_length(), harmonic)
+            .into_iter()
+            .map(|anti_node| anti_node - self.compensation)
+            .collect()
+    }
+
+    /// Interior nodes of `harmonic` for the note being played, in mm from the bridge line
+    pub(crate) fn nodes(&self, harmonic: u8) -> Vec<f32> {
+        get_nodes_for_harmonic(self.vibrating_length(), harmonic)
+            .into_iter()
+            .map(|node| node - self.compensation)
+            .collect()
+    }
+
+    /// The fretted note vibrates between the bridge and the fret, so anti-nodes are laid out
+    /// over the shortened length while the pickup stays put
+    fn fretted_score(&self, fret: u8, pos: f32) -> f32 {
+        self.objective_score(fretted_length(self.length, fret) + self.compensation, pos)
+    }
+
+    fn objective_score(&self, vibrating_length: f32, pos: f32) -> f32 {
+        self.objective
+            .evaluate(&self.responses(vibrating_length, pos), &self.weights)
+    }
+
+    /// Response of harmonics 2-7 at `pos`. Clamped terminations pull every mode shape in by
+    /// about one bending length at each end, and the sharpened upper partials carry less
+    /// energy, approximated by scaling each response by 1 / (1 + B n^2).
+    /// `pos` is from the bridge line and `vibrating_length` from the saddle.
+    pub(crate) fn responses(&self, vibrating_length: f32, pos: f32) -> [f32; 6] {
+        let pos = pos + self.compensation;
+        if self.bending_length <= 0.0 {
+            return harmonic_falloffs(vibrating_length, pos);
+        }
+
+        let b = inharmonicity(self.bending_length, vibrating_length);
+        let mut responses = harmonic_falloffs(
+            vibrating_length - 2.0 * self.bending_length,
+            pos - self.bending_length,
+        );
+        for (response, harmonic) in responses.iter_mut().zip(2..=7_u8) {
+            *response /= 1.0 + b * (harmonic as f32).powi(2);
+        }
+        responses
+    }
+}
+
+/// Vibrating length from the bridge when the string is stopped at `fret` (equal temperament)
+pub(crate) fn fretted_length(length: f32, fret: u8) -> f32 {
+    length / 2.0_f32.powf(fret as f32 / 12.0)
+}
+
+#[derive(Default)]
 pub(crate) struct OptimalPositions {
     pub(crate) bridge_position: f32,
     pub(crate) neck_position: f32,
 }
 
+/// Weight given to the strongest harmonic when weights are derived rather than set by hand
+pub(crate) const STRONGEST_WEIGHT: f32 = 1.5;
+
+/// Scale derived weights so the strongest harmonic gets `STRONGEST_WEIGHT`
+pub(crate) fn normalize_weights(weights: &mut [f32; 6]) {
+    let max = weights.iter().cloned().fold(0.0_f32, f32::max);
+    if max > 0.0 {
+        weights
+            .iter_mut()
+            .for_each(|w| *w = *w / max * STRONGEST_WEIGHT);
+    }
+}
+
 pub(crate) fn get_anti_nodes_for_harmonic(length: f32, harmonic: u8) -> Vec<f32> {
     let segment_length = length / harmonic as f32;
 
@@ -11,10 +242,72 @@ pub(crate) fn get_anti_nodes_for_harmonic(length: f32, harmonic: u8) -> Vec<f32>
         .collect()
 }
 
+/// Interior nodes only; the bridge and the stopping point are nodes of every harmonic
+pub(crate) fn get_nodes_for_harmonic(length: f32, harmonic: u8) -> Vec<f32> {
+    let segment_length = length / harmonic as f32;
+
+    (1..harmonic).map(|i| i as f32 * segment_length).collect()
+}
+
+/// Harmonics weighted at least this much are warned about by default when a pickup nears a
+/// node. Low enough that the lightly weighted H2 of the default guitar still counts.
+pub(crate) const DEFAULT_NODE_WARNING_WEIGHT: f32 = 0.1;
+
+/// Harmonics weighted at least `min_weight` with a node within `distance` of `pos`,
+/// paired with how far away that node is
+pub(crate) fn nearby_nodes(
+    model: &ScoreModel,
+    pos: f32,
+    distance: f32,
+    min_weight: f32,
+) -> Vec<(u8, f32)> {
+    (2..=7_u8)
+        .zip(model.weights.iter())
+        .filter(|&(_, &weight)| weight >= min_weight)
+        .filter_map(|(harmonic, _)| {
+            model
+                .nodes(harmonic)
+                .into_iter()
+                .map(|node| (pos - node).abs())
+                .min_by(|a, b| a.partial_cmp(b).unwrap())
+                .filter(|&gap| gap <= distance)
+                .map(|gap| (harmonic, gap))
+        })
+        .collect()
+}
+
+/// Cosine falloff of harmonics 2-7 around their nearest anti-nodes: 1 on an anti-node, 0 on a node
+pub(crate) fn harmonic_falloffs(length: f32, pos: f32) -> [f32; 6] {
+    let mut falloffs = [0.0; 6];
+    for (falloff, harmonic) in falloffs.iter_mut().zip(2..=7_u8) {
+        let min_dist = get_anti_nodes_for_harmonic(length, harmonic)
+            .into_iter()
+            .map(|anti_node| (pos - anti_node).abs())
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+            .unwrap();
+
+        // Sine wave falloff: use cosine for smooth bell curve
+        // The wavelength determines how far the influence extends
+        let wavelength = length / (harmonic as f32 * 2.0);
+        let normalized_dist = (min_dist / wavelength).min(1.0);
+        *falloff = (normalized_dist * std::f32::consts::PI / 2.0).cos();
+    }
+    falloffs
+}
+
 pub(crate) fn find_optimal_pickup_positions(
+    model: &ScoreModel,
+    search_limit: usize,
+) -> OptimalPositions {
+    find_optimal_positions_by(model.length, search_limit, |pos| model.score(pos))
+}
+
+/// Peak search shared by every pickup type; `score` rates a pickup centered at a position,
+/// or returns None where there is no signal, which leaves that position out of the search
+pub(crate) fn find_optimal_positions_by(
     length: f32,
-    weights: &[f32; 6],
     search_limit: usize,
+    score: impl Fn(f32) -> Option<f32>,
 ) -> OptimalPositions {
     // Search in the first 50% of string length from bridge (typical pickup placement)
     // let search_limit = (length * 0.5) as usize;
@@ -22,27 +315,9 @@ pub(crate) fn find_optimal_pickup_positions(
 
     // Calculate score at each position
     let scores: Vec<(f32, f32)> = (0..=search_limit)
-        .map(|i| {
+        .filter_map(|i| {
             let pos = (i as f32 / resolution as f32) * length;
-            let score: f32 = (2..=7_u8)
-                .zip(weights.iter())
-                .map(|(harmonic, &weight)| {
-                    let min_dist = get_anti_nodes_for_harmonic(length, harmonic)
-                        .into_iter()
-                        .map(|anti_node| (pos - anti_node).abs())
-                        .min_by(|a, b| a.partial_cmp(b).unwrap())
-                        .unwrap();
-
-                    // Use same sine wave falloff as heat map
-                    let wavelength = length / (harmonic as f32 * 2.0);
-                    let normalized_dist = (min_dist / wavelength).min(1.0);
-                    let falloff = (normalized_dist * std::f32::consts::PI / 2.0).cos();
-
-                    weight * falloff
-                })
-                .sum();
-
-            (pos, score)
+            score(pos).map(|score| (pos, score))
         })
         .collect();
 
@@ -53,6 +328,14 @@ pub(crate) fn find_optimal_pickup_positions(
         .max_by(|(_, (_, a)), (_, (_, b))| a.partial_cmp(b).unwrap())
         .unwrap();
 
+    // "Dropped by half" is measured against the lowest score rather than zero,
+    // since some objectives score below zero
+    let min_score = scores
+        .iter()
+        .map(|&(_, score)| score)
+        .fold(f32::INFINITY, f32::min);
+    let half_drop = (bridge_score - min_score) * 0.5;
+
     // Exclude region around the bridge peak
     // We need to expand outward from the peak until values stop decreasing
     // Use a minimum exclusion window as well (e.g., 10% of string length)
@@ -72,7 +355,7 @@ pub(crate) fn find_optimal_pickup_positions(
         }
         if (bridge_pos - scores[i].0).abs() >= min_exclusion_distance {
             // We've gone far enough to consider this outside the peak region
-            if curr_score < bridge_score * 0.5 {
+            if bridge_score - curr_score > half_drop {
                 // If we've dropped below 50% of peak, we're definitely clear
                 break;
             }
@@ -83,15 +366,14 @@ pub(crate) fn find_optimal_pickup_positions(
 
     // Walk right
     prev_score = bridge_score;
-    for i in (bridge_idx + 1)..scores.len() {
-        let curr_score = scores[i].1;
+    for (i, &(pos, curr_score)) in scores.iter().enumerate().skip(bridge_idx + 1) {
         if curr_score > prev_score {
             // Score started increasing again, stop here
             break;
         }
-        if (scores[i].0 - bridge_pos).abs() >= min_exclusion_distance {
+        if (pos - bridge_pos).abs() >= min_exclusion_distance {
             // We've gone far enough to consider this outside the peak region
-            if curr_score < bridge_score * 0.5 {
+            if bridge_score - curr_score > half_drop {
                 // If we've dropped below 50% of peak, we're definitely clear
                 break;
             }
@@ -114,3 +396,163 @@ pub(crate) fn find_optimal_pickup_positions(
         neck_position: neck_pos,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSES: [f32; 6] = [1.0, 0.5, 0.0, 0.25, 0.75, 1.0];
+    const WEIGHTS: [f32; 6] = [1.0, 2.0, 0.0, -1.0, 0.5, 0.0];
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "got {}, expected {}",
+            actual,
+            expected
+        );
+    }
+
+    fn model(fret_mode: FretMode, objective: Objective) -> ScoreModel {
+        ScoreModel {
+            length: 650.0,
+            weights: [0.15, 1.50, 1.50, 1.50, 0.75, 0.75],
+            fret_mode,
+            bending_length: 0.0,
+            objective,
+            compensation: 0.0,
+        }
+    }
+
+    #[test]
+    fn weighted_sum() {
+        // 1 + 1 + 0 - 0.25 + 0.375 + 0
+        assert_close(Objective::WeightedSum.evaluate(&RESPONSES, &WEIGHTS), 2.125);
+    }
+
+    #[test]
+    fn target_suppress() {
+        let objective = Objective::TargetSuppress {
+            target: 3,
+            suppress: 2,
+        };
+        assert_close(objective.evaluate(&RESPONSES, &WEIGHTS), 0.5 - 1.0);
+
+        // Nothing left to suppress, so the target is simply favored
+        let same = Objective::TargetSuppress {
+            target: 3,
+            suppress: 3,
+        };
+        assert_close(same.evaluate(&RESPONSES, &WEIGHTS), 0.5);
+
+        // Harmonics outside 2-7 contribute nothing
+        let outside = Objective::TargetSuppress {
+            target: 9,
+            suppress: 2,
+        };
+        assert_close(outside.evaluate(&RESPONSES, &WEIGHTS), -1.0);
+    }
+
+    #[test]
+    fn max_min_ignores_unweighted_harmonics() {
+        // Positive weights on H2, H3 and H6: min(1.0, 0.5, 0.75)
+        assert_close(Objective::MaxMin.evaluate(&RESPONSES, &WEIGHTS), 0.5);
+        assert_close(Objective::MaxMin.evaluate(&RESPONSES, &[0.0; 6]), 0.0);
+    }
+
+    #[test]
+    fn match_shape() {
+        // Targets [0.5, 1, 0, 0, 0.25, 0]; squared errors sum to 1.8125
+        assert_close(
+            Objective::MatchShape.evaluate(&RESPONSES, &WEIGHTS),
+            -(1.8125_f32 / 6.0).sqrt(),
+        );
+        // A perfect match scores zero
+        let responses = [0.5, 1.0, 0.0, 0.0, 0.25, 0.0];
+        assert_close(Objective::MatchShape.evaluate(&responses, &WEIGHTS), 0.0);
+    }
+
+    #[test]
+    fn match_shape_targets_nodes_when_no_weight_is_positive() {
+        // Every target is zero, so the score is minus the RMS response
+        assert_close(
+            Objective::MatchShape.evaluate(&RESPONSES, &[-1.0; 6]),
+            -(2.875_f32 / 6.0).sqrt(),
+        );
+        assert_close(Objective::MatchShape.evaluate(&[0.0; 6], &[-1.0; 6]), 0.0);
+    }
+
+    #[test]
+    fn avoid_nodes() {
+        // -(0 + 2 * 0.5^4 + 0 - 0.75^4 + 0.5 * 0.25^4 + 0)
+        assert_close(
+            Objective::AvoidNodes.evaluate(&RESPONSES, &WEIGHTS),
+            0.189_453_13,
+        );
+        assert_close(Objective::AvoidNodes.evaluate(&[1.0; 6], &WEIGHTS), 0.0);
+    }
+
+    #[test]
+    fn twelfth_fret_halves_the_length() {
+        assert_close(fretted_length(650.0, 12), 325.0);
+        assert_close(fretted_length(650.0, 24), 162.5);
+        assert_close(fretted_length(650.0, 0), 650.0);
+    }
+
+    #[test]
+    fn nodes_and_anti_nodes() {
+        assert_eq!(
+            get_anti_nodes_for_harmonic(600.0, 3),
+            vec![100.0, 300.0, 500.0]
+        );
+        assert_eq!(get_nodes_for_harmonic(600.0, 3), vec![200.0, 400.0]);
+        assert_eq!(get_anti_nodes_for_harmonic(600.0, 2), vec![150.0, 450.0]);
+        assert_eq!(get_nodes_for_harmonic(600.0, 2), vec![300.0]);
+    }
+
+    #[test]
+    fn compensation_shifts_nodes_towards_the_bridge() {
+        let model = ScoreModel {
+            length: 597.0,
+            compensation: 3.0,
+            ..model(FretMode::Open, Objective::WeightedSum)
+        };
+        // The string vibrates over 600 mm starting 3 mm behind the bridge line
+        assert_eq!(model.nodes(3), vec![197.0, 397.0]);
+        assert_eq!(model.anti_nodes(2), vec![147.0, 447.0]);
+    }
+
+    #[test]
+    fn no_score_past_the_stopping_fret() {
+        let model = model(FretMode::Fretted(24), Objective::AvoidNodes);
+        let stop = fretted_length(650.0, 24);
+        assert!(model.score(stop - 1.0).is_some());
+        assert!(model.score(stop + 1.0).is_none());
+
+        let average = ScoreModel {
+            fret_mode: FretMode::AverageUpTo(12),
+            ..model
+        };
+        assert!(average.score(320.0).is_some());
+        assert!(average.score(330.0).is_none());
+    }
+
+    #[test]
+    fn optimum_stays_below_the_stopping_fret() {
+        // Every AvoidNodes score is at most zero, so scoring "no signal" as zero would win
+        for objective in [Objective::AvoidNodes, Objective::MatchShape] {
+            let model = model(FretMode::Fretted(24), objective);
+            let stop = fretted_length(650.0, 24);
+            let positions = find_optimal_pickup_positions(&model, 325);
+            assert!(positions.bridge_position <= stop);
+            assert!(positions.neck_position <= stop);
+        }
+
+        let negative = ScoreModel {
+            weights: [-1.0; 6],
+            ..model(FretMode::Fretted(24), Objective::WeightedSum)
+        };
+        let positions = find_optimal_pickup_positions(&negative, 325);
+        assert!(positions.bridge_position <= fretted_length(650.0, 24));
+    }
+}
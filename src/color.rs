@@ -1,10 +1,21 @@
-use egui::Color32;
+use egui::{Color32, ColorImage};
 use palette::{IntoColor, Mix, Oklab, Srgb};
+use serde::{Deserialize, Serialize};
 
 pub(crate) const HEATMAP_COLORS: [i32; 7] = [
     0x000000, 0x0000FF, 0x00FFFF, 0x00FF00, 0xFFFF00, 0xFF0000, 0xFFFFFF,
 ];
 
+pub(crate) const VIRIDIS_COLORS: [i32; 5] = [0x440154, 0x3B528B, 0x21918C, 0x5EC962, 0xFDE725];
+pub(crate) const MAGMA_COLORS: [i32; 5] = [0x000004, 0x51127C, 0xB73779, 0xFC8961, 0xFCFDBF];
+pub(crate) const GRAYSCALE_COLORS: [i32; 2] = [0x000000, 0xFFFFFF];
+
+/// Blue below zero through black at zero to the warm end of the heat colors above it
+pub(crate) const DIVERGING_COLORS: [i32; 5] = [0x00FFFF, 0x0000FF, 0x000000, 0xFF0000, 0xFFFF00];
+
+/// Positions with no signal (NaN), kept out of every gradient so they never read as a score
+const NO_SIGNAL_COLOR: Color32 = Color32::from_rgb(48, 40, 56);
+
 /// Extension trait to create Srgb from hex color codes
 pub(crate) trait ColorExt {
     fn parse_hex(hex: u32) -> Self;
@@ -28,15 +39,136 @@ impl ColorExt for Color32 {
     }
 }
 
-pub(crate) fn heat_to_color(normalized_heat: f32, hex_colors: &[i32]) -> Color32 {
-    let heat = normalized_heat.clamp(0.0, 1.0);
+/// Colormap sampled into a fixed lookup table so per-sample lookups are just an index
+pub(crate) struct ColorGradient {
+    lut: Vec<Color32>,
+}
+
+impl ColorGradient {
+    const LUT_SIZE: usize = 512;
+
+    pub(crate) fn from_hex(hex_colors: &[i32]) -> Self {
+        // Convert hex values to Oklab colors once, up front
+        let color_stops: Vec<Oklab> = hex_colors
+            .iter()
+            .map(|&hex| Srgb::parse_hex(hex as u32).into_color())
+            .collect();
+
+        let lut = (0..Self::LUT_SIZE)
+            .map(|i| interpolate_stops(&color_stops, i as f32 / (Self::LUT_SIZE - 1) as f32))
+            .collect();
+
+        Self { lut }
+    }
+
+    pub(crate) fn sample(&self, normalized_heat: f32) -> Color32 {
+        let heat = normalized_heat.clamp(0.0, 1.0);
+        let idx = (heat * (self.lut.len() - 1) as f32).round() as usize;
+        self.lut[idx]
+    }
+
+    /// Map a row-major grid of values to pixels, normalizing `range` onto the gradient
+    pub(crate) fn image(&self, values: &[f32], size: [usize; 2], range: (f32, f32)) -> ColorImage {
+        let (min, max) = range;
+        let span = max - min;
+        let pixels = values
+            .iter()
+            .map(|&value| {
+                if value.is_nan() {
+                    return NO_SIGNAL_COLOR;
+                }
+                let normalized = if span > 0.0 {
+                    (value - min) / span
+                } else {
+                    0.0
+                };
+                self.sample(normalized)
+            })
+            .collect();
+
+        ColorImage::new(size, pixels)
+    }
+}
+
+/// Choice of sequential colormap for the heat maps
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Colormap {
+    Classic,
+    Viridis,
+    Magma,
+    Grayscale,
+}
+
+impl Colormap {
+    pub(crate) const ALL: [Colormap; 4] = [
+        Colormap::Classic,
+        Colormap::Viridis,
+        Colormap::Magma,
+        Colormap::Grayscale,
+    ];
 
-    // Convert hex values to Oklab colors
-    let color_stops: Vec<Oklab> = hex_colors
-        .iter()
-        .map(|&hex| Srgb::parse_hex(hex as u32).into_color())
-        .collect();
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Colormap::Classic => "Classic",
+            Colormap::Viridis => "Viridis",
+            Colormap::Magma => "Magma",
+            Colormap::Grayscale => "Grayscale",
+        }
+    }
+
+    fn hex_colors(&self) -> &'static [i32] {
+        match self {
+            Colormap::Classic => &HEATMAP_COLORS,
+            Colormap::Viridis => &VIRIDIS_COLORS,
+            Colormap::Magma => &MAGMA_COLORS,
+            Colormap::Grayscale => &GRAYSCALE_COLORS,
+        }
+    }
+}
+
+/// Sequential colormap for scores that never go negative, and a diverging one centered on
+/// zero for when negative weights or objectives push some scores below it
+pub(crate) struct HeatPalette {
+    pub(crate) sequential: ColorGradient,
+    pub(crate) diverging: ColorGradient,
+}
 
+impl HeatPalette {
+    pub(crate) fn new(colormap: Colormap) -> Self {
+        Self {
+            sequential: ColorGradient::from_hex(colormap.hex_colors()),
+            diverging: ColorGradient::from_hex(&DIVERGING_COLORS),
+        }
+    }
+
+    /// Gradient and value range to map `range` with, widened to be symmetric when diverging
+    /// so zero always lands on the center color
+    pub(crate) fn scale(&self, range: (f32, f32)) -> (&ColorGradient, (f32, f32)) {
+        let (min, max) = range;
+        if min < 0.0 {
+            let extent = max.max(-min);
+            (&self.diverging, (-extent, extent))
+        } else {
+            (&self.sequential, range)
+        }
+    }
+
+    pub(crate) fn image(&self, values: &[f32], size: [usize; 2], range: (f32, f32)) -> ColorImage {
+        let (gradient, range) = self.scale(range);
+        gradient.image(values, size, range)
+    }
+}
+
+fn oklab_to_color32(oklab: Oklab) -> Color32 {
+    let rgb: Srgb = oklab.into_color();
+    Color32::from_rgb(
+        (rgb.red * 255.0) as u8,
+        (rgb.green * 255.0) as u8,
+        (rgb.blue * 255.0) as u8,
+    )
+}
+
+fn interpolate_stops(color_stops: &[Oklab], heat: f32) -> Color32 {
     let num_stops = color_stops.len();
 
     // Handle edge cases
@@ -44,12 +176,7 @@ pub(crate) fn heat_to_color(normalized_heat: f32, hex_colors: &[i32]) -> Color32
         return Color32::BLACK;
     }
     if num_stops == 1 {
-        let rgb: Srgb = color_stops[0].into_color();
-        return Color32::from_rgb(
-            (rgb.red * 255.0) as u8,
-            (rgb.green * 255.0) as u8,
-            (rgb.blue * 255.0) as u8,
-        );
+        return oklab_to_color32(color_stops[0]);
     }
 
     // Calculate which segment we're in
@@ -65,14 +192,5 @@ pub(crate) fn heat_to_color(normalized_heat: f32, hex_colors: &[i32]) -> Color32
     let t = ((heat - segment_start) / segment_size).clamp(0.0, 1.0);
 
     // Interpolate in Oklab space
-    let oklab = color_stops[lower_idx].mix(color_stops[upper_idx], t);
-
-    // Convert back to sRGB
-    let rgb: Srgb = oklab.into_color();
-
-    Color32::from_rgb(
-        (rgb.red * 255.0) as u8,
-        (rgb.green * 255.0) as u8,
-        (rgb.blue * 255.0) as u8,
-    )
+    oklab_to_color32(color_stops[lower_idx].mix(color_stops[upper_idx], t))
 }
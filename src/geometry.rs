@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+use crate::calculation::fretted_length;
+
+/// Where the fretboard and saddle sit relative to the nominal scale, in mm
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct NeckGeometry {
+    pub(crate) fret_count: u8,
+    /// How far the neck pocket edge sits past the last fret, towards the bridge
+    pub(crate) pocket_overhang: f32,
+    /// How far the saddle contact point sits behind the nominal bridge line
+    pub(crate) saddle_compensation: f32,
+}
+
+impl Default for NeckGeometry {
+    fn default() -> Self {
+        Self {
+            fret_count: 22,
+            pocket_overhang: 12.0,
+            saddle_compensation: 2.5,
+        }
+    }
+}
+
+/// A point positions can be measured from
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ReferenceFrame {
+    Bridge,
+    Saddle,
+    NeckPocket,
+    LastFret,
+    Nut,
+}
+
+impl ReferenceFrame {
+    pub(crate) const ALL: [ReferenceFrame; 5] = [
+        ReferenceFrame::Bridge,
+        ReferenceFrame::Saddle,
+        ReferenceFrame::NeckPocket,
+        ReferenceFrame::LastFret,
+        ReferenceFrame::Nut,
+    ];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            ReferenceFrame::Bridge => "Bridge",
+            ReferenceFrame::Saddle => "Saddle",
+            ReferenceFrame::NeckPocket => "Neck pocket",
+            ReferenceFrame::LastFret => "Last fret",
+            ReferenceFrame::Nut => "Nut",
+        }
+    }
+
+    /// The reference point in mm from the nominal bridge line, positive towards the nut
+    pub(crate) fn origin(&self, length: f32, geometry: &NeckGeometry) -> f32 {
+        let last_fret = fretted_length(length, geometry.fret_count);
+        match self {
+            ReferenceFrame::Bridge => 0.0,
+            ReferenceFrame::Saddle => -geometry.saddle_compensation,
+            ReferenceFrame::NeckPocket => last_fret - geometry.pocket_overhang,
+            ReferenceFrame::LastFret => last_fret,
+            ReferenceFrame::Nut => length,
+        }
+    }
+
+    /// Signed offset from the reference point to `pos` (mm from the bridge): positive when
+    /// `pos` is on the nut side of the reference point, negative on the bridge side
+    pub(crate) fn offset(&self, pos: f32, length: f32, geometry: &NeckGeometry) -> f32 {
+        pos - self.origin(length, geometry)
+    }
+}
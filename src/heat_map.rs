@@ -0,0 +1,319 @@
+use egui::{Color32, Pos2, Rect, TextureHandle, TextureOptions};
+
+use crate::blend::{BlendPickup, blend_score};
+use crate::calculation::{OptimalPositions, ScoreModel, find_optimal_pickup_positions};
+use crate::color::HeatPalette;
+
+/// Inputs the heat map depends on; a change in any of them invalidates the cache
+#[derive(Clone, Copy, PartialEq)]
+struct HeatMapKey {
+    model: ScoreModel,
+    range: (f32, f32),
+    resolution: usize,
+}
+
+/// Heat map samples across the visible part of the string, recomputed only when the inputs
+/// change. Zooming in spends the full resolution on the narrower range.
+#[derive(Default)]
+pub(crate) struct HeatMap {
+    pub(crate) values: Vec<f32>,
+    pub(crate) range: (f32, f32),
+    key: Option<HeatMapKey>,
+}
+
+impl HeatMap {
+    /// Recompute the samples if any input differs from the cached ones.
+    /// Returns true when the data changed.
+    pub(crate) fn update(
+        &mut self,
+        model: &ScoreModel,
+        visible: (f32, f32),
+        resolution: usize,
+    ) -> bool {
+        let key = HeatMapKey {
+            model: *model,
+            range: visible,
+            resolution,
+        };
+        if self.key == Some(key) {
+            return false;
+        }
+
+        let (start, end) = visible;
+        self.values = (0..resolution)
+            .map(|i| {
+                let pos = start + (i as f32 + 0.5) / resolution as f32 * (end - start);
+                model.score(pos).unwrap_or(f32::NAN)
+            })
+            .collect();
+        self.range = heat_range(&self.values);
+        self.key = Some(key);
+
+        true
+    }
+}
+
+/// Heat map grid with one row per string and x spanning the longest string.
+/// Each row is scaled by its own largest magnitude so every string's optimum stands out
+/// while zero stays zero,
+/// and positions past a shorter string's nut are left without a value.
+#[derive(Default)]
+pub(crate) struct StringSetHeatMap {
+    pub(crate) values: Vec<f32>,
+    pub(crate) size: [usize; 2],
+    pub(crate) max_length: f32,
+    pub(crate) range: (f32, f32),
+    key: Option<(Vec<ScoreModel>, usize)>,
+}
+
+impl StringSetHeatMap {
+    /// `models` holds one scoring model per string. Returns true when the data changed.
+    pub(crate) fn update(&mut self, models: &[ScoreModel], resolution: usize) -> bool {
+        if self
+            .key
+            .as_ref()
+            .is_some_and(|(cached, res)| cached == models && *res == resolution)
+        {
+            return false;
+        }
+
+        let max_length = models.iter().map(|m| m.length).fold(0.0_f32, f32::max);
+        let mut values = Vec::with_capacity(resolution * models.len());
+
+        for model in models {
+            let row_start = values.len();
+            values.extend(score_row(model, max_length, resolution));
+
+            let row = &mut values[row_start..];
+            let (row_min, row_max) = heat_range(row);
+            let extent = row_max.max(-row_min);
+            if extent > 0.0 {
+                row.iter_mut().for_each(|v| *v /= extent);
+            }
+        }
+
+        self.values = values;
+        self.range = heat_range(&self.values);
+        self.size = [resolution, models.len()];
+        self.max_length = max_length;
+        self.key = Some((models.to_vec(), resolution));
+
+        true
+    }
+}
+
+/// Inputs the scale sweep depends on; the model's own length is ignored
+#[derive(Clone, Copy, PartialEq)]
+struct ScaleSweepKey {
+    model: ScoreModel,
+    min_length: f32,
+    max_length: f32,
+    rows: usize,
+    resolution: usize,
+    search_limit: usize,
+}
+
+/// Score over a (position, string length) grid: x is distance from the bridge up to
+/// the longest swept scale, y steps from the shortest to the longest scale.
+/// The optimum positions for every row are kept alongside so they can be traced.
+#[derive(Default)]
+pub(crate) struct ScaleSweepHeatMap {
+    pub(crate) values: Vec<f32>,
+    pub(crate) size: [usize; 2],
+    pub(crate) range: (f32, f32),
+    pub(crate) lengths: Vec<f32>,
+    pub(crate) optima: Vec<OptimalPositions>,
+    key: Option<ScaleSweepKey>,
+}
+
+impl ScaleSweepHeatMap {
+    /// Returns true when the data changed
+    pub(crate) fn update(
+        &mut self,
+        model: &ScoreModel,
+        length_range: (f32, f32),
+        rows: usize,
+        resolution: usize,
+        search_limit: usize,
+    ) -> bool {
+        let (min_length, max_length) = length_range;
+        let key = ScaleSweepKey {
+            model: ScoreModel {
+                length: 0.0,
+                ..*model
+            },
+            min_length,
+            max_length,
+            rows,
+            resolution,
+            search_limit,
+        };
+        if self.key == Some(key) {
+            return false;
+        }
+
+        self.lengths = (0..rows)
+            .map(|row| {
+                let t = if rows > 1 {
+                    row as f32 / (rows - 1) as f32
+                } else {
+                    0.0
+                };
+                min_length + t * (max_length - min_length)
+            })
+            .collect();
+        let models: Vec<ScoreModel> = self
+            .lengths
+            .iter()
+            .map(|&length| ScoreModel { length, ..*model })
+            .collect();
+        self.values = models
+            .iter()
+            .flat_map(|model| score_row(model, max_length, resolution))
+            .collect();
+        self.optima = models
+            .iter()
+            .map(|model| find_optimal_pickup_positions(model, search_limit))
+            .collect();
+        self.range = heat_range(&self.values);
+        self.size = [resolution, rows];
+        self.key = Some(key);
+
+        true
+    }
+}
+
+/// Blend score as the first pickup is swept along the string with the others held in place
+#[derive(Default)]
+pub(crate) struct BlendHeatMap {
+    pub(crate) values: Vec<f32>,
+    pub(crate) range: (f32, f32),
+    key: Option<(ScoreModel, Vec<BlendPickup>, usize)>,
+}
+
+impl BlendHeatMap {
+    /// Returns true when the data changed
+    pub(crate) fn update(
+        &mut self,
+        model: &ScoreModel,
+        pickups: &[BlendPickup],
+        resolution: usize,
+    ) -> bool {
+        if self
+            .key
+            .as_ref()
+            .is_some_and(|(cached_model, cached, res)| {
+                cached_model == model && cached == pickups && *res == resolution
+            })
+        {
+            return false;
+        }
+
+        let mut swept = pickups.to_vec();
+        self.values = (0..resolution)
+            .map(|i| {
+                let pos = (i as f32 / resolution as f32) * model.length;
+                if let Some(first) = swept.first_mut() {
+                    first.position = pos;
+                }
+                blend_score(model, &swept)
+            })
+            .collect();
+        self.range = heat_range(&self.values);
+        self.key = Some((*model, pickups.to_vec(), resolution));
+
+        true
+    }
+}
+
+/// Min and max of the values, always including zero so a score of zero maps to the same
+/// end of the gradient whether or not anything dips below it. NaN (no signal) is skipped.
+fn heat_range(values: &[f32]) -> (f32, f32) {
+    values.iter().fold((0.0_f32, 0.0_f32), |(min, max), &v| {
+        (min.min(v), max.max(v))
+    })
+}
+
+/// Scores for one string sampled across `0..axis_length`, NaN wherever it has no signal
+fn score_row(
+    model: &ScoreModel,
+    axis_length: f32,
+    resolution: usize,
+) -> impl Iterator<Item = f32> + '_ {
+    (0..resolution).map(move |i| {
+        let pos = (i as f32 / resolution as f32) * axis_length;
+        model.score(pos).unwrap_or(f32::NAN)
+    })
+}
+
+/// GPU texture holding a rendered grid of heat values.
+/// Only re-uploaded when the caller has new data, so drawing it is a single quad.
+pub(crate) struct HeatTexture {
+    handle: Option<TextureHandle>,
+    options: TextureOptions,
+}
+
+impl Default for HeatTexture {
+    fn default() -> Self {
+        Self::with_options(TextureOptions::LINEAR)
+    }
+}
+
+impl HeatTexture {
+    /// Use `TextureOptions::NEAREST` for grids with discrete rows (e.g. one per string)
+    pub(crate) fn with_options(options: TextureOptions) -> Self {
+        Self {
+            handle: None,
+            options,
+        }
+    }
+
+    /// Render `values` (row-major, `size` = [width, height]) through `gradient` and upload it.
+    /// Grids wider or taller than the GPU allows are reduced by keeping the peak of each bucket.
+    pub(crate) fn upload(
+        &mut self,
+        ctx: &egui::Context,
+        name: &str,
+        values: &[f32],
+        size: [usize; 2],
+        range: (f32, f32),
+        palette: &HeatPalette,
+    ) {
+        let max_side = ctx.input(|i| i.max_texture_side);
+        let (values, size) = downsample_max(values, size, max_side);
+        let image = palette.image(&values, size, range);
+
+        match &mut self.handle {
+            Some(handle) => handle.set(image, self.options),
+            None => self.handle = Some(ctx.load_texture(name, image, self.options)),
+        }
+    }
+
+    pub(crate) fn paint(&self, painter: &egui::Painter, rect: Rect) {
+        if let Some(handle) = &self.handle {
+            let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+            painter.image(handle.id(), rect, uv, Color32::WHITE);
+        }
+    }
+}
+
+fn downsample_max(values: &[f32], size: [usize; 2], max_side: usize) -> (Vec<f32>, [usize; 2]) {
+    let [width, height] = size;
+    let new_width = width.min(max_side).max(1);
+    let new_height = height.min(max_side).max(1);
+    if new_width == width && new_height == height {
+        return (values.to_vec(), size);
+    }
+
+    let mut reduced = vec![f32::NEG_INFINITY; new_width * new_height];
+    for y in 0..height {
+        let ry = y * new_height / height;
+        for x in 0..width {
+            let rx = x * new_width / width;
+            let cell = &mut reduced[ry * new_width + rx];
+            *cell = cell.max(values[y * width + x]);
+        }
+    }
+
+    (reduced, [new_width, new_height])
+}
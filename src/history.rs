@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+use crate::blend::BlendPickup;
+use crate::calculation::{
+    DEFAULT_NODE_WARNING_WEIGHT, FretMode, Objective, OptimalPositions, StringConfig,
+};
+use crate::geometry::NeckGeometry;
+use crate::stiffness::StringStiffness;
+use crate::string_set::StringSpec;
+
+/// Oldest states are dropped past this many
+const MAX_ENTRIES: usize = 200;
+
+/// Every parameter undo/redo restores. View state (zoom, layout, units) is left alone.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Params {
+    pub(crate) string_length: f32,
+    pub(crate) weights: [f32; 6],
+    pub(crate) fret_mode: FretMode,
+    pub(crate) objective: Objective,
+    pub(crate) stiffness_enabled: bool,
+    pub(crate) stiffness: StringStiffness,
+    pub(crate) humbucker_enabled: bool,
+    pub(crate) coil_spacing: f32,
+    pub(crate) search_limit: usize,
+    pub(crate) tuning_frequency: f32,
+    pub(crate) node_warning_distance: f32,
+    #[serde(default = "default_node_warning_weight")]
+    pub(crate) node_warning_weight: f32,
+    #[serde(default)]
+    pub(crate) geometry: NeckGeometry,
+    pub(crate) strings: Vec<StringConfig>,
+    pub(crate) string_specs: Vec<StringSpec>,
+    pub(crate) sweep_range: (f32, f32),
+    pub(crate) sweep_rows: usize,
+    pub(crate) blend_pickups: Vec<BlendPickup>,
+}
+
+fn default_node_warning_weight() -> f32 {
+    DEFAULT_NODE_WARNING_WEIGHT
+}
+
+/// A recorded state along with the pickup positions it produced, for the history list
+pub(crate) struct HistoryEntry {
+    pub(crate) params: Params,
+    pub(crate) bridge_position: f32,
+    pub(crate) neck_position: f32,
+}
+
+/// Linear undo stack. Recording after an undo discards the states that could have been redone.
+#[derive(Default)]
+pub(crate) struct History {
+    pub(crate) entries: Vec<HistoryEntry>,
+    pub(crate) current: usize,
+}
+
+impl History {
+    fn current(&self) -> Option<&Params> {
+        self.entries.get(self.current).map(|entry| &entry.params)
+    }
+
+    /// Push `params` as the new current state unless nothing changed
+    pub(crate) fn record(&mut self, params: Params, positions: &OptimalPositions) {
+        if self.current() == Some(&params) {
+            return;
+        }
+
+        self.entries.truncate(self.current + 1);
+        self.entries.push(HistoryEntry {
+            params,
+            bridge_position: positions.bridge_position,
+            neck_position: positions.neck_position,
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.current = self.entries.len() - 1;
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        self.current > 0
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        self.current + 1 < self.entries.len()
+    }
+
+    /// Move to entry `index` and return its parameters, if it exists
+    pub(crate) fn jump(&mut self, index: usize) -> Option<Params> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        self.current = index;
+        Some(self.entries[index].params.clone())
+    }
+
+    pub(crate) fn undo(&mut self) -> Option<Params> {
+        self.can_undo()
+            .then(|| self.jump(self.current - 1))
+            .flatten()
+    }
+
+    pub(crate) fn redo(&mut self) -> Option<Params> {
+        self.can_redo()
+            .then(|| self.jump(self.current + 1))
+            .flatten()
+    }
+}
@@ -0,0 +1,71 @@
+use std::f32::consts::PI;
+
+use crate::blend::{BlendPickup, blend_score};
+use crate::calculation::{OptimalPositions, ScoreModel, find_optimal_positions_by};
+use crate::response::amplitude_to_db;
+
+/// Center of a humbucker and the positions of its two coils, in mm from the bridge
+#[derive(Clone, Copy, Default)]
+pub(crate) struct HumbuckerPlacement {
+    pub(crate) center: f32,
+    pub(crate) coils: [f32; 2],
+}
+
+impl HumbuckerPlacement {
+    fn new(center: f32, coil_spacing: f32) -> Self {
+        Self {
+            center,
+            coils: [center - coil_spacing / 2.0, center + coil_spacing / 2.0],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct HumbuckerPositions {
+    pub(crate) bridge: HumbuckerPlacement,
+    pub(crate) neck: HumbuckerPlacement,
+}
+
+/// Both coils of a humbucker wired in series and in phase, each contributing half
+fn coils(center: f32, coil_spacing: f32) -> [BlendPickup; 2] {
+    HumbuckerPlacement::new(center, coil_spacing)
+        .coils
+        .map(|position| BlendPickup {
+            position,
+            level: 0.5,
+            reversed: false,
+        })
+}
+
+/// Best humbucker centers for a fixed coil spacing, using the same peak search as single
+/// coils but scoring the summed response of both coils
+pub(crate) fn find_optimal_humbucker_positions(
+    model: &ScoreModel,
+    coil_spacing: f32,
+    search_limit: usize,
+) -> HumbuckerPositions {
+    let OptimalPositions {
+        bridge_position,
+        neck_position,
+    } = find_optimal_positions_by(model.length, search_limit, |center| {
+        (center + coil_spacing / 2.0 <= model.scored_length())
+            .then(|| blend_score(model, &coils(center, coil_spacing)))
+    });
+
+    HumbuckerPositions {
+        bridge: HumbuckerPlacement::new(bridge_position, coil_spacing),
+        neck: HumbuckerPlacement::new(neck_position, coil_spacing),
+    }
+}
+
+/// Level of each harmonic 1..=count relative to a single coil at the humbucker's center.
+/// Summing sin(k(c - d)) and sin(k(c + d)) gives 2 sin(kc) cos(kd), so the coil spacing
+/// alone imposes a cos(n pi s / 2L) comb regardless of where the pickup sits.
+pub(crate) fn coil_spacing_attenuation(length: f32, coil_spacing: f32, count: u8) -> Vec<f32> {
+    (1..=count)
+        .map(|harmonic| {
+            let factor = (harmonic as f32 * PI * coil_spacing / (2.0 * length)).cos();
+            amplitude_to_db(factor.abs())
+        })
+        .collect()
+}
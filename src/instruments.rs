@@ -0,0 +1,190 @@
+use crate::string_set::{PRESETS, StringSpec, WrapMaterial};
+
+/// Typical build of one kind of instrument, used to set up every part of the app at once
+pub(crate) struct Instrument {
+    pub(crate) name: &'static str,
+    /// Range of scale lengths in mm commonly found on this instrument
+    pub(crate) scale_range: (f32, f32),
+    pub(crate) default_scale: f32,
+    /// Highest string first, like the string set presets
+    pub(crate) strings: &'static [StringSpec],
+    pub(crate) weights: [f32; 6], // Harmonics 2-7
+    pub(crate) pickup_count: usize,
+    /// How far from the bridge to search for pickup positions, in thousandths of the scale
+    pub(crate) search_limit: usize,
+}
+
+impl Instrument {
+    /// The string with the lowest open pitch
+    pub(crate) fn lowest_string(&self) -> &StringSpec {
+        self.strings
+            .iter()
+            .min_by(|a, b| a.frequency().total_cmp(&b.frequency()))
+            .expect("every instrument has strings")
+    }
+}
+
+// Basses favor the lower harmonics that carry the fundamental's weight; short-scale and
+// bowed instruments keep more of the upper ones, which give them their character.
+const GUITAR_WEIGHTS: [f32; 6] = [0.15, 1.50, 1.50, 1.50, 0.75, 0.75];
+const BARITONE_WEIGHTS: [f32; 6] = [0.30, 1.50, 1.50, 1.20, 0.60, 0.50];
+const BASS_WEIGHTS: [f32; 6] = [1.50, 1.50, 1.00, 0.75, 0.40, 0.30];
+const MANDOLIN_WEIGHTS: [f32; 6] = [0.30, 1.20, 1.50, 1.50, 1.00, 0.80];
+const UKULELE_WEIGHTS: [f32; 6] = [0.50, 1.50, 1.50, 1.00, 0.60, 0.40];
+const BOWED_WEIGHTS: [f32; 6] = [1.00, 1.50, 1.50, 1.20, 1.00, 0.80];
+
+const UKULELE_STRINGS: &[StringSpec] = &[
+    StringSpec::nylon(0.024, 69),
+    StringSpec::nylon(0.031, 64),
+    StringSpec::nylon(0.036, 60),
+    StringSpec::nylon(0.026, 67), // Re-entrant high G
+];
+
+pub(crate) const INSTRUMENTS: [Instrument; 12] = [
+    Instrument {
+        name: "Electric Guitar",
+        scale_range: (610.0, 686.0),
+        default_scale: 648.0,
+        strings: PRESETS[0].strings,
+        weights: GUITAR_WEIGHTS,
+        pickup_count: 2,
+        search_limit: 325,
+    },
+    Instrument {
+        name: "Baritone Guitar",
+        scale_range: (686.0, 762.0),
+        default_scale: 711.0,
+        strings: &[
+            StringSpec::plain(0.013, 59),
+            StringSpec::plain(0.017, 54),
+            StringSpec::wound(0.026, 0.0135, WrapMaterial::NickelPlatedSteel, 50),
+            StringSpec::wound(0.036, 0.0155, WrapMaterial::NickelPlatedSteel, 45),
+            StringSpec::wound(0.046, 0.018, WrapMaterial::NickelPlatedSteel, 40),
+            StringSpec::wound(0.062, 0.022, WrapMaterial::NickelPlatedSteel, 35),
+        ],
+        weights: BARITONE_WEIGHTS,
+        pickup_count: 2,
+        search_limit: 325,
+    },
+    Instrument {
+        name: "4-String Bass",
+        scale_range: (762.0, 889.0),
+        default_scale: 864.0,
+        strings: PRESETS[2].strings,
+        weights: BASS_WEIGHTS,
+        pickup_count: 2,
+        search_limit: 350,
+    },
+    Instrument {
+        name: "5-String Bass",
+        scale_range: (864.0, 940.0),
+        default_scale: 889.0,
+        strings: &[
+            StringSpec::wound(0.045, 0.020, WrapMaterial::NickelPlatedSteel, 43),
+            StringSpec::wound(0.065, 0.025, WrapMaterial::NickelPlatedSteel, 38),
+            StringSpec::wound(0.085, 0.030, WrapMaterial::NickelPlatedSteel, 33),
+            StringSpec::wound(0.105, 0.035, WrapMaterial::NickelPlatedSteel, 28),
+            StringSpec::wound(0.130, 0.040, WrapMaterial::NickelPlatedSteel, 23),
+        ],
+        weights: BASS_WEIGHTS,
+        pickup_count: 2,
+        search_limit: 350,
+    },
+    Instrument {
+        name: "Mandolin",
+        scale_range: (330.0, 356.0),
+        default_scale: 349.0,
+        strings: &[
+            StringSpec::plain(0.011, 76),
+            StringSpec::plain(0.015, 69),
+            StringSpec::wound(0.026, 0.012, WrapMaterial::PhosphorBronze, 62),
+            StringSpec::wound(0.040, 0.016, WrapMaterial::PhosphorBronze, 55),
+        ],
+        weights: MANDOLIN_WEIGHTS,
+        pickup_count: 1,
+        search_limit: 250,
+    },
+    Instrument {
+        name: "Soprano Ukulele",
+        scale_range: (320.0, 350.0),
+        default_scale: 330.0,
+        strings: UKULELE_STRINGS,
+        weights: UKULELE_WEIGHTS,
+        pickup_count: 1,
+        search_limit: 250,
+    },
+    Instrument {
+        name: "Concert Ukulele",
+        scale_range: (370.0, 395.0),
+        default_scale: 380.0,
+        strings: UKULELE_STRINGS,
+        weights: UKULELE_WEIGHTS,
+        pickup_count: 1,
+        search_limit: 250,
+    },
+    Instrument {
+        name: "Tenor Ukulele",
+        scale_range: (420.0, 445.0),
+        default_scale: 430.0,
+        strings: UKULELE_STRINGS,
+        weights: UKULELE_WEIGHTS,
+        pickup_count: 1,
+        search_limit: 250,
+    },
+    Instrument {
+        name: "Violin",
+        scale_range: (325.0, 332.0),
+        default_scale: 328.0,
+        strings: &[
+            StringSpec::plain(0.010, 76),
+            StringSpec::wound(0.014, 0.010, WrapMaterial::StainlessSteel, 69),
+            StringSpec::wound(0.020, 0.012, WrapMaterial::StainlessSteel, 62),
+            StringSpec::wound(0.030, 0.014, WrapMaterial::StainlessSteel, 55),
+        ],
+        weights: BOWED_WEIGHTS,
+        pickup_count: 1,
+        search_limit: 200,
+    },
+    Instrument {
+        name: "Viola",
+        scale_range: (355.0, 390.0),
+        default_scale: 370.0,
+        strings: &[
+            StringSpec::wound(0.016, 0.011, WrapMaterial::StainlessSteel, 69),
+            StringSpec::wound(0.022, 0.013, WrapMaterial::StainlessSteel, 62),
+            StringSpec::wound(0.032, 0.015, WrapMaterial::StainlessSteel, 55),
+            StringSpec::wound(0.042, 0.017, WrapMaterial::StainlessSteel, 48),
+        ],
+        weights: BOWED_WEIGHTS,
+        pickup_count: 1,
+        search_limit: 200,
+    },
+    Instrument {
+        name: "Cello",
+        scale_range: (680.0, 700.0),
+        default_scale: 690.0,
+        strings: &[
+            StringSpec::wound(0.030, 0.018, WrapMaterial::StainlessSteel, 57),
+            StringSpec::wound(0.040, 0.022, WrapMaterial::StainlessSteel, 50),
+            StringSpec::wound(0.052, 0.026, WrapMaterial::StainlessSteel, 43),
+            StringSpec::wound(0.068, 0.030, WrapMaterial::StainlessSteel, 36),
+        ],
+        weights: BOWED_WEIGHTS,
+        pickup_count: 1,
+        search_limit: 200,
+    },
+    Instrument {
+        name: "Double Bass",
+        scale_range: (1020.0, 1100.0),
+        default_scale: 1060.0,
+        strings: &[
+            StringSpec::wound(0.063, 0.030, WrapMaterial::StainlessSteel, 43),
+            StringSpec::wound(0.078, 0.035, WrapMaterial::StainlessSteel, 38),
+            StringSpec::wound(0.095, 0.040, WrapMaterial::StainlessSteel, 33),
+            StringSpec::wound(0.115, 0.045, WrapMaterial::StainlessSteel, 28),
+        ],
+        weights: BOWED_WEIGHTS,
+        pickup_count: 1,
+        search_limit: 200,
+    },
+];
@@ -2,18 +2,32 @@ use crate::app::HarmonicApp;
 use eframe::egui;
 
 mod app;
+mod blend;
 mod calculation;
 mod color;
+mod geometry;
+mod heat_map;
+mod history;
+mod humbucker;
+mod instruments;
+mod response;
+mod spectrum;
+mod stiffness;
+mod string_set;
+mod synth;
+mod units;
 mod visualizer;
 
 fn main() -> eframe::Result {
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([706.0, 678.0]),
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([1100.0, 720.0])
+            .with_min_inner_size([640.0, 420.0]),
         ..Default::default()
     };
     eframe::run_native(
         "Harmonic Anti-Node Visualizer",
         options,
-        Box::new(|_cc| Ok(Box::new(HarmonicApp::default()))),
+        Box::new(|cc| Ok(Box::new(HarmonicApp::new(cc)))),
     )
 }
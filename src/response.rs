@@ -0,0 +1,59 @@
+use std::f32::consts::PI;
+
+use crate::calculation::ScoreModel;
+use crate::stiffness::inharmonicity;
+
+/// Lowest level reported, so exact nodes don't plot at negative infinity
+pub(crate) const DB_FLOOR: f32 = -60.0;
+
+pub(crate) struct ResponsePoint {
+    pub(crate) frequency: f32,
+    pub(crate) db: f32,
+}
+
+/// What a single-coil pickup at `pos` (mm from the bridge) passes of each partial 1..=count.
+/// The pickup sees each mode's displacement at its location, |sin(n pi x / L)|, so the
+/// result is a comb filter with notches where harmonics have nodes.
+pub(crate) fn pickup_response(
+    model: &ScoreModel,
+    pos: f32,
+    open_frequency: f32,
+    count: u8,
+) -> Vec<ResponsePoint> {
+    (1..=count)
+        .map(|harmonic| ResponsePoint {
+            frequency: partial_frequency(model, open_frequency, harmonic),
+            db: amplitude_to_db(mode_shape(model, harmonic, pos).abs()),
+        })
+        .collect()
+}
+
+/// Signed displacement of mode `harmonic` at `pos` for the note being played.
+/// On a stiff string the mode is pulled in by one bending length at each termination.
+/// `pos` is from the bridge line, so the saddle compensation is added back on.
+pub(crate) fn mode_shape(model: &ScoreModel, harmonic: u8, pos: f32) -> f32 {
+    let effective_length = model.vibrating_length() - 2.0 * model.bending_length;
+    let effective_pos = pos + model.compensation - model.bending_length;
+    if effective_pos <= 0.0 || effective_pos >= effective_length {
+        return 0.0;
+    }
+    (harmonic as f32 * PI * effective_pos / effective_length).sin()
+}
+
+/// Frequency of partial `harmonic` of the note being played, sharpened by stiffness.
+/// `open_frequency` is the open-string pitch; shorter fretted lengths raise it.
+pub(crate) fn partial_frequency(model: &ScoreModel, open_frequency: f32, harmonic: u8) -> f32 {
+    let vibrating_length = model.vibrating_length();
+    let f0 = open_frequency * (model.length + model.compensation) / vibrating_length;
+    let b = inharmonicity(model.bending_length, vibrating_length);
+    let n = harmonic as f32;
+    n * f0 * (1.0 + b * n * n).sqrt()
+}
+
+pub(crate) fn amplitude_to_db(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        DB_FLOOR
+    } else {
+        (20.0 * amplitude.log10()).max(DB_FLOOR)
+    }
+}
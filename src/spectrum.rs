@@ -0,0 +1,348 @@
+use std::f32::consts::PI;
+use std::fmt;
+use std::path::Path;
+
+use crate::calculation::normalize_weights;
+
+/// Partials 1..=7: the fundamental plus the harmonics that have weights
+pub(crate) const MEASURED_PARTIALS: u8 = 7;
+
+/// Fewest periods of the fundamental the analysis window must hold to resolve the partials
+const MIN_PERIODS: f32 = 20.0;
+
+/// RMS level below which a recording counts as silent (-60 dBFS)
+const SILENCE_RMS: f32 = 0.001;
+
+/// Share of the signal's power the measured partials must carry, or the fundamental is wrong
+const MIN_PARTIAL_POWER: f32 = 0.05;
+
+pub(crate) enum ImportError {
+    Io(std::io::Error),
+    Wav(hound::Error),
+    Parse(String),
+    Analysis(String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(err) => write!(f, "{}", err),
+            ImportError::Wav(err) => write!(f, "{}", err),
+            ImportError::Parse(msg) | ImportError::Analysis(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(err: std::io::Error) -> Self {
+        ImportError::Io(err)
+    }
+}
+
+impl From<hound::Error> for ImportError {
+    fn from(err: hound::Error) -> Self {
+        ImportError::Wav(err)
+    }
+}
+
+/// Linear amplitudes of partials 1..=MEASURED_PARTIALS, index 0 being the fundamental
+pub(crate) struct MeasuredSpectrum {
+    pub(crate) amplitudes: Vec<f32>,
+}
+
+impl MeasuredSpectrum {
+    /// Weights for harmonics 2-7 in proportion to how strongly each was measured
+    pub(crate) fn weights(&self) -> [f32; 6] {
+        let mut weights = [0.0; 6];
+        for (weight, &amplitude) in weights.iter_mut().zip(self.amplitudes.iter().skip(1)) {
+            *weight = amplitude;
+        }
+        normalize_weights(&mut weights);
+        weights
+    }
+}
+
+/// Import a WAV recording or a CSV of harmonic magnitudes, chosen by file extension
+pub(crate) fn import_spectrum(
+    path: &Path,
+    fundamental: f32,
+) -> Result<MeasuredSpectrum, ImportError> {
+    let is_csv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+
+    if is_csv {
+        read_harmonic_csv(path)
+    } else {
+        let (samples, sample_rate) = read_wav_mono(path)?;
+        analyze_recording(&samples, sample_rate, fundamental)
+    }
+}
+
+/// Read a WAV file of any bit depth, averaging all channels down to mono
+fn read_wav_mono(path: &Path) -> Result<(Vec<f32>, u32), ImportError> {
+    let mut reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 * scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    let channels = spec.channels.max(1) as usize;
+    let mono = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    Ok((mono, spec.sample_rate))
+}
+
+/// Measure each partial's level from a recorded note with a known fundamental.
+/// The attack is skipped and up to a second of the sustain is analyzed through a Hann window.
+/// Each partial is searched for within +/-2% of n * f0, since stiff strings run sharp.
+/// Recordings that are too short, silent or carry little energy at the partials are rejected
+/// rather than turned into meaningless weights.
+fn analyze_recording(
+    samples: &[f32],
+    sample_rate: u32,
+    fundamental: f32,
+) -> Result<MeasuredSpectrum, ImportError> {
+    let rate = sample_rate as f32;
+    if !(fundamental > 0.0 && 2.0 * fundamental < rate / 2.0) {
+        return Err(ImportError::Analysis(format!(
+            "open string pitch {:.1} Hz cannot be analyzed at {} Hz",
+            fundamental, sample_rate
+        )));
+    }
+
+    let start = ((0.05 * rate) as usize).min(samples.len());
+    let end = (start + rate as usize).min(samples.len());
+    let window = &samples[start..end];
+
+    let min_len = (MIN_PERIODS * rate / fundamental).ceil() as usize;
+    if window.len() < min_len {
+        return Err(ImportError::Analysis(format!(
+            "recording too short: need at least {:.0} ms after the first 50 ms",
+            min_len as f32 / rate * 1000.0
+        )));
+    }
+
+    let power = window.iter().map(|s| s * s).sum::<f32>() / window.len() as f32;
+    if power.sqrt() < SILENCE_RMS {
+        return Err(ImportError::Analysis("recording is silent".to_owned()));
+    }
+
+    let len = window.len() as f32;
+    let windowed: Vec<f32> = window
+        .iter()
+        .enumerate()
+        .map(|(i, &s)| s * (0.5 - 0.5 * (2.0 * PI * i as f32 / (len - 1.0)).cos()))
+        .collect();
+
+    let amplitudes = (1..=MEASURED_PARTIALS)
+        .map(|harmonic| {
+            let center = harmonic as f32 * fundamental;
+            (-10..=10)
+                .map(|step| center * (1.0 + step as f32 * 0.002))
+                .filter(|&frequency| frequency < rate / 2.0)
+                .map(|frequency| goertzel_magnitude(&windowed, rate, frequency))
+                .fold(0.0_f32, f32::max)
+        })
+        .collect::<Vec<f32>>();
+
+    // A Hann-windowed sine of amplitude A reads A * len / 4, and carries A^2 / 2 of power
+    let partial_power: f32 = amplitudes
+        .iter()
+        .map(|magnitude| (magnitude * 4.0 / len).powi(2) / 2.0)
+        .sum();
+    if partial_power < MIN_PARTIAL_POWER * power {
+        return Err(ImportError::Analysis(format!(
+            "little energy at multiples of {:.1} Hz; check the open string pitch",
+            fundamental
+        )));
+    }
+
+    Ok(MeasuredSpectrum { amplitudes })
+}
+
+/// Magnitude of a single DFT bin at an arbitrary frequency
+fn goertzel_magnitude(samples: &[f32], sample_rate: f32, frequency: f32) -> f32 {
+    let omega = 2.0 * PI * frequency / sample_rate;
+    let coeff = 2.0 * omega.cos();
+    let (mut s1, mut s2) = (0.0_f32, 0.0_f32);
+    for &sample in samples {
+        let s0 = sample + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0).sqrt()
+}
+
+/// Lines of `harmonic,magnitude`. Magnitudes are linear unless the header mentions dB.
+/// Blank lines and lines starting with `#` are ignored.
+fn read_harmonic_csv(path: &Path) -> Result<MeasuredSpectrum, ImportError> {
+    let text = std::fs::read_to_string(path)?;
+    let mut amplitudes = vec![0.0; MEASURED_PARTIALS as usize];
+    let mut in_db = false;
+    let mut seen_data = false;
+
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split([',', ';', '\t']).map(str::trim);
+        let (Some(first), Some(second)) = (fields.next(), fields.next()) else {
+            return Err(ImportError::Parse(format!(
+                "line {}: expected harmonic,magnitude",
+                line_no + 1
+            )));
+        };
+
+        let (Ok(harmonic), Ok(magnitude)) = (first.parse::<u8>(), second.parse::<f32>()) else {
+            if !seen_data {
+                // Header row
+                in_db = line.to_ascii_lowercase().contains("db");
+                continue;
+            }
+            return Err(ImportError::Parse(format!(
+                "line {}: could not parse '{}'",
+                line_no + 1,
+                line
+            )));
+        };
+
+        seen_data = true;
+        if (1..=MEASURED_PARTIALS).contains(&harmonic) {
+            amplitudes[harmonic as usize - 1] = if in_db {
+                10.0_f32.powf(magnitude / 20.0)
+            } else {
+                magnitude.abs()
+            };
+        }
+    }
+
+    if amplitudes.iter().skip(1).all(|&a| a == 0.0) {
+        return Err(ImportError::Parse(
+            "no magnitudes found for harmonics 2-7".to_owned(),
+        ));
+    }
+
+    Ok(MeasuredSpectrum { amplitudes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 44_100;
+    const F0: f32 = 110.0;
+    /// Levels of partials 1-7 in the synthesized note
+    const LEVELS: [f32; 7] = [1.0, 0.8, 0.4, 0.2, 0.1, 0.3, 0.05];
+
+    fn note(seconds: f32, fundamental: f32) -> Vec<f32> {
+        (0..(seconds * RATE as f32) as usize)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                LEVELS
+                    .iter()
+                    .enumerate()
+                    .map(|(n, level)| {
+                        0.2 * level * (2.0 * PI * (n + 1) as f32 * fundamental * t).sin()
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn analysis_error(samples: &[f32], fundamental: f32) -> String {
+        match analyze_recording(samples, RATE, fundamental) {
+            Ok(_) => panic!("analysis should have been rejected"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    fn read_csv(name: &str, contents: &str) -> Result<MeasuredSpectrum, ImportError> {
+        let path = std::env::temp_dir().join(format!("bpcalc_test_{}.csv", name));
+        std::fs::write(&path, contents).unwrap();
+        let result = import_spectrum(&path, F0);
+        std::fs::remove_file(&path).unwrap();
+        result
+    }
+
+    #[test]
+    fn recovers_partial_levels_from_a_sine_mix() {
+        let Ok(spectrum) = analyze_recording(&note(1.5, F0), RATE, F0) else {
+            panic!("a clean note should analyze");
+        };
+        for (amplitude, level) in spectrum.amplitudes.iter().zip(LEVELS) {
+            let ratio = amplitude / spectrum.amplitudes[0];
+            assert!((ratio - level).abs() < 0.02, "{} vs {}", ratio, level);
+        }
+
+        // H2-H7 weights in proportion to the levels, strongest at 1.5
+        for (weight, level) in spectrum.weights().iter().zip(&LEVELS[1..]) {
+            let expected = level / 0.8 * 1.5;
+            assert!(
+                (weight - expected).abs() < 0.05,
+                "{} vs {}",
+                weight,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unusable_recordings() {
+        assert!(analysis_error(&vec![0.0; RATE as usize], F0).contains("silent"));
+        assert!(analysis_error(&note(0.1, F0), F0).contains("too short"));
+        assert!(analysis_error(&note(1.5, F0), 173.0).contains("little energy"));
+        assert!(analysis_error(&note(1.5, F0), 0.0).contains("cannot be analyzed"));
+        assert!(analysis_error(&note(1.5, F0), 15_000.0).contains("cannot be analyzed"));
+    }
+
+    #[test]
+    fn parses_linear_csv() {
+        let csv = "harmonic,magnitude\n# comment\n1,1.0\n2,0.5\n\n3;0.25\n4\t-0.125\n9,3.0\n";
+        let Ok(spectrum) = read_csv("linear", csv) else {
+            panic!("valid CSV should parse");
+        };
+        assert_eq!(
+            spectrum.amplitudes,
+            vec![1.0, 0.5, 0.25, 0.125, 0.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn parses_db_csv() {
+        let Ok(spectrum) = read_csv("db", "Harmonic,Level (dB)\n2,0\n3,-20\n") else {
+            panic!("valid CSV should parse");
+        };
+        assert!((spectrum.amplitudes[1] - 1.0).abs() < 1e-6);
+        assert!((spectrum.amplitudes[2] - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rejects_bad_csv() {
+        assert!(matches!(
+            read_csv("bad_value", "2,0.5\n3,loud\n"),
+            Err(ImportError::Parse(msg)) if msg.starts_with("line 2")
+        ));
+        assert!(matches!(
+            read_csv("one_field", "2\n"),
+            Err(ImportError::Parse(_))
+        ));
+        assert!(matches!(
+            read_csv("fundamental_only", "1,1.0\n"),
+            Err(ImportError::Parse(_))
+        ));
+    }
+}
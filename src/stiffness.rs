@@ -0,0 +1,145 @@
+use std::f32::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::units::MM_PER_INCH;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Material {
+    PlainSteel,
+    StainlessSteel,
+    Nylon,
+}
+
+impl Material {
+    pub(crate) const ALL: [Material; 3] = [
+        Material::PlainSteel,
+        Material::StainlessSteel,
+        Material::Nylon,
+    ];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Material::PlainSteel => "Steel",
+            Material::StainlessSteel => "Stainless Steel",
+            Material::Nylon => "Nylon",
+        }
+    }
+
+    /// Young's modulus in Pa
+    pub(crate) fn youngs_modulus(&self) -> f32 {
+        match self {
+            Material::PlainSteel => 200e9,
+            Material::StainlessSteel => 193e9,
+            Material::Nylon => 4e9,
+        }
+    }
+
+    /// Density in kg/m^3
+    pub(crate) fn density(&self) -> f32 {
+        match self {
+            Material::PlainSteel => 7850.0,
+            Material::StainlessSteel => 8000.0,
+            Material::Nylon => 1140.0,
+        }
+    }
+}
+
+/// Physical properties of a string that set its bending stiffness
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct StringStiffness {
+    pub(crate) gauge_in: f32,
+    /// Core wire diameter; equal to the gauge for plain strings
+    pub(crate) core_in: f32,
+    pub(crate) tension_n: f32,
+    pub(crate) material: Material,
+}
+
+impl Default for StringStiffness {
+    fn default() -> Self {
+        // A typical wound low E (.046 on a .018 hex core)
+        Self {
+            gauge_in: 0.046,
+            core_in: 0.018,
+            tension_n: 80.0,
+            material: Material::PlainSteel,
+        }
+    }
+}
+
+impl StringStiffness {
+    /// The wrap on a wound string adds mass but almost no bending stiffness,
+    /// so only the core counts; a plain string is all core.
+    fn stiffness_diameter_m(&self) -> f32 {
+        self.core_in.min(self.gauge_in) * MM_PER_INCH / 1000.0
+    }
+
+    /// Characteristic bending length sqrt(EI/T) in mm.
+    /// Independent of vibrating length, so it holds for fretted notes too.
+    pub(crate) fn bending_length(&self) -> f32 {
+        if self.tension_n <= 0.0 {
+            return 0.0;
+        }
+        let d = self.stiffness_diameter_m();
+        let second_moment = PI * d.powi(4) / 64.0;
+        (self.material.youngs_modulus() * second_moment / self.tension_n).sqrt() * 1000.0
+    }
+}
+
+/// Inharmonicity coefficient B for a vibrating length in mm, so that
+/// partial n sounds at n * f0 * sqrt(1 + B * n^2)
+pub(crate) fn inharmonicity(bending_length: f32, length: f32) -> f32 {
+    if length <= 0.0 {
+        return 0.0;
+    }
+    (PI * bending_length / length).powi(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bending_length_matches_euler_bernoulli() {
+        // .010 plain steel at 72.6 N: sqrt(E * pi d^4 / 64 / T)
+        let stiffness = StringStiffness {
+            gauge_in: 0.010,
+            core_in: 0.010,
+            tension_n: 72.6,
+            material: Material::PlainSteel,
+        };
+        let d = 0.010 * 0.0254_f64;
+        let expected = (200e9 * std::f64::consts::PI * d.powi(4) / 64.0 / 72.6).sqrt() * 1000.0;
+        assert!((stiffness.bending_length() as f64 - expected).abs() < 1e-3);
+        assert!((stiffness.bending_length() - 0.750).abs() < 0.005);
+    }
+
+    #[test]
+    fn only_the_core_of_a_wound_string_bends() {
+        let wound = StringStiffness::default();
+        let core_only = StringStiffness {
+            gauge_in: wound.core_in,
+            ..wound
+        };
+        assert_eq!(wound.bending_length(), core_only.bending_length());
+    }
+
+    #[test]
+    fn slack_string_has_no_bending_length() {
+        let stiffness = StringStiffness {
+            tension_n: 0.0,
+            ..StringStiffness::default()
+        };
+        assert_eq!(stiffness.bending_length(), 0.0);
+    }
+
+    #[test]
+    fn inharmonicity_is_pi_bending_length_over_length_squared() {
+        let b = inharmonicity(2.0, 650.0);
+        assert!((b - (PI * 2.0 / 650.0).powi(2)).abs() < 1e-9);
+        assert!((b - 9.344e-5).abs() < 1e-7);
+        // Halving the length quadruples B
+        assert!((inharmonicity(2.0, 325.0) / b - 4.0).abs() < 1e-4);
+        assert_eq!(inharmonicity(2.0, 0.0), 0.0);
+    }
+}
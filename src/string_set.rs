@@ -0,0 +1,273 @@
+use std::f32::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::calculation::normalize_weights;
+use crate::stiffness::{Material, StringStiffness, inharmonicity};
+use crate::units::MM_PER_INCH;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) enum WrapMaterial {
+    NickelPlatedSteel,
+    PureNickel,
+    StainlessSteel,
+    PhosphorBronze,
+    Bronze8020,
+}
+
+impl WrapMaterial {
+    pub(crate) const ALL: [WrapMaterial; 5] = [
+        WrapMaterial::NickelPlatedSteel,
+        WrapMaterial::PureNickel,
+        WrapMaterial::StainlessSteel,
+        WrapMaterial::PhosphorBronze,
+        WrapMaterial::Bronze8020,
+    ];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            WrapMaterial::NickelPlatedSteel => "Nickel-Plated Steel",
+            WrapMaterial::PureNickel => "Pure Nickel",
+            WrapMaterial::StainlessSteel => "Stainless Steel",
+            WrapMaterial::PhosphorBronze => "Phosphor Bronze",
+            WrapMaterial::Bronze8020 => "80/20 Bronze",
+        }
+    }
+
+    /// Density in kg/m^3
+    fn density(&self) -> f32 {
+        match self {
+            WrapMaterial::NickelPlatedSteel => 7950.0,
+            WrapMaterial::PureNickel => 8900.0,
+            WrapMaterial::StainlessSteel => 8000.0,
+            WrapMaterial::PhosphorBronze => 8800.0,
+            WrapMaterial::Bronze8020 => 8600.0,
+        }
+    }
+}
+
+/// One string as bought: its gauge, construction and the note it is tuned to
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct StringSpec {
+    pub(crate) gauge_in: f32,
+    /// Core wire diameter; ignored for plain strings
+    pub(crate) core_in: f32,
+    pub(crate) core: Material,
+    /// `None` for a plain (unwound) string
+    pub(crate) wrap: Option<WrapMaterial>,
+    /// Open-string pitch as a MIDI note number (A4 = 69)
+    pub(crate) midi_note: u8,
+}
+
+impl StringSpec {
+    pub(crate) const fn plain(gauge_in: f32, midi_note: u8) -> Self {
+        Self {
+            gauge_in,
+            core_in: gauge_in,
+            core: Material::PlainSteel,
+            wrap: None,
+            midi_note,
+        }
+    }
+
+    pub(crate) const fn wound(
+        gauge_in: f32,
+        core_in: f32,
+        wrap: WrapMaterial,
+        midi_note: u8,
+    ) -> Self {
+        Self {
+            gauge_in,
+            core_in,
+            core: Material::PlainSteel,
+            wrap: Some(wrap),
+            midi_note,
+        }
+    }
+
+    /// Plain monofilament, as on ukuleles and classical trebles
+    pub(crate) const fn nylon(gauge_in: f32, midi_note: u8) -> Self {
+        Self {
+            gauge_in,
+            core_in: gauge_in,
+            core: Material::Nylon,
+            wrap: None,
+            midi_note,
+        }
+    }
+
+    pub(crate) fn frequency(&self) -> f32 {
+        440.0 * 2.0_f32.powf((self.midi_note as f32 - 69.0) / 12.0)
+    }
+
+    /// Mass per unit length in kg/m. A wound string's wrap is treated as a close helix of
+    /// round wire, which adds rho * pi^2 * w * (core + w) / 4 on top of the core.
+    fn unit_weight(&self) -> f32 {
+        let core_d = self.core_diameter_in() * MM_PER_INCH / 1000.0;
+        let core = self.core.density() * PI * core_d * core_d / 4.0;
+
+        match self.wrap {
+            None => core,
+            Some(wrap) => {
+                let wrap_d =
+                    (self.gauge_in - self.core_diameter_in()).max(0.0) / 2.0 * MM_PER_INCH / 1000.0;
+                core + wrap.density() * PI * PI * wrap_d * (core_d + wrap_d) / 4.0
+            }
+        }
+    }
+
+    fn core_diameter_in(&self) -> f32 {
+        match self.wrap {
+            None => self.gauge_in,
+            Some(_) => self.core_in.min(self.gauge_in),
+        }
+    }
+
+    /// Tension in N needed to reach the tuned pitch over `length` mm (Mersenne's law)
+    pub(crate) fn tension(&self, length: f32) -> f32 {
+        let length_m = length / 1000.0;
+        self.unit_weight() * (2.0 * length_m * self.frequency()).powi(2)
+    }
+
+    pub(crate) fn stiffness(&self, length: f32) -> StringStiffness {
+        StringStiffness {
+            gauge_in: self.gauge_in,
+            core_in: self.core_diameter_in(),
+            tension_n: self.tension(length),
+            material: self.core,
+        }
+    }
+
+    /// Starting weights for harmonics 2-7: how audible each partial is (A-weighting at its
+    /// frequency) times how much of it survives stiffness damping, scaled so the strongest
+    /// harmonic gets 1.5.
+    pub(crate) fn suggested_weights(&self, length: f32) -> [f32; 6] {
+        let b = inharmonicity(self.stiffness(length).bending_length(), length);
+        let f0 = self.frequency();
+
+        let mut weights = [0.0; 6];
+        for (weight, harmonic) in weights.iter_mut().zip(2..=7_u8) {
+            let n = harmonic as f32;
+            let partial = n * f0 * (1.0 + b * n * n).sqrt();
+            *weight = a_weighting(partial) / (1.0 + b * n * n);
+        }
+
+        normalize_weights(&mut weights);
+        weights
+    }
+}
+
+/// Linear A-weighting gain (IEC 61672), about 1.0 at 1 kHz
+fn a_weighting(f: f32) -> f32 {
+    let f2 = f * f;
+    let num = 12194.0_f32.powi(2) * f2 * f2;
+    let den = (f2 + 20.6_f32.powi(2))
+        * ((f2 + 107.7_f32.powi(2)) * (f2 + 737.9_f32.powi(2))).sqrt()
+        * (f2 + 12194.0_f32.powi(2));
+    num / den * 1.2589
+}
+
+/// Standard string sets, listed from the highest string to the lowest
+pub(crate) struct StringSetPreset {
+    pub(crate) name: &'static str,
+    pub(crate) strings: &'static [StringSpec],
+}
+
+pub(crate) const PRESETS: [StringSetPreset; 3] = [
+    StringSetPreset {
+        name: "Electric 10-46 (E Standard)",
+        strings: &[
+            StringSpec::plain(0.010, 64),
+            StringSpec::plain(0.013, 59),
+            StringSpec::plain(0.017, 55),
+            StringSpec::wound(0.026, 0.0135, WrapMaterial::NickelPlatedSteel, 50),
+            StringSpec::wound(0.036, 0.0155, WrapMaterial::NickelPlatedSteel, 45),
+            StringSpec::wound(0.046, 0.018, WrapMaterial::NickelPlatedSteel, 40),
+        ],
+    },
+    StringSetPreset {
+        name: "Acoustic 12-53 (E Standard)",
+        strings: &[
+            StringSpec::plain(0.012, 64),
+            StringSpec::plain(0.016, 59),
+            StringSpec::wound(0.024, 0.012, WrapMaterial::PhosphorBronze, 55),
+            StringSpec::wound(0.032, 0.014, WrapMaterial::PhosphorBronze, 50),
+            StringSpec::wound(0.042, 0.017, WrapMaterial::PhosphorBronze, 45),
+            StringSpec::wound(0.053, 0.020, WrapMaterial::PhosphorBronze, 40),
+        ],
+    },
+    StringSetPreset {
+        name: "Bass 45-105 (E Standard)",
+        strings: &[
+            StringSpec::wound(0.045, 0.020, WrapMaterial::NickelPlatedSteel, 43),
+            StringSpec::wound(0.065, 0.025, WrapMaterial::NickelPlatedSteel, 38),
+            StringSpec::wound(0.085, 0.030, WrapMaterial::NickelPlatedSteel, 33),
+            StringSpec::wound(0.105, 0.035, WrapMaterial::NickelPlatedSteel, 28),
+        ],
+    },
+];
+
+pub(crate) fn note_name(midi_note: u8) -> String {
+    const NAMES: [&str; 12] = [
+        "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+    ];
+    let octave = midi_note as i32 / 12 - 1;
+    format!("{}{}", NAMES[midi_note as usize % 12], octave)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculation::STRONGEST_WEIGHT;
+
+    const KGF: f32 = 9.80665;
+
+    #[test]
+    fn plain_e4_tension_matches_published_value() {
+        // A .010 plain steel E4 at 25.5" is published at about 7.4 kgf (16.2 lb)
+        let tension = StringSpec::plain(0.010, 64).tension(648.0) / KGF;
+        assert!((tension - 7.4).abs() < 0.15, "{} kgf", tension);
+    }
+
+    #[test]
+    fn wound_e2_tension_matches_published_value() {
+        // A .046 nickel-wound E2 on a .018 core at 25.5" is published at about 7.9 kgf
+        let spec = StringSpec::wound(0.046, 0.018, WrapMaterial::NickelPlatedSteel, 40);
+        let tension = spec.tension(648.0) / KGF;
+        assert!((tension - 7.9).abs() < 0.3, "{} kgf", tension);
+    }
+
+    #[test]
+    fn tension_scales_with_length_squared() {
+        let spec = StringSpec::plain(0.010, 64);
+        let ratio = spec.tension(700.0) / spec.tension(350.0);
+        assert!((ratio - 4.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn frequency_follows_equal_temperament() {
+        assert!((StringSpec::plain(0.010, 69).frequency() - 440.0).abs() < 1e-3);
+        assert!((StringSpec::plain(0.010, 64).frequency() - 329.63).abs() < 0.01);
+        assert!((StringSpec::plain(0.046, 40).frequency() - 82.41).abs() < 0.01);
+    }
+
+    #[test]
+    fn suggested_weights_peak_at_strongest_weight() {
+        for spec in PRESETS[0].strings {
+            let weights = spec.suggested_weights(648.0);
+            let max = weights.iter().cloned().fold(0.0_f32, f32::max);
+            assert!((max - STRONGEST_WEIGHT).abs() < 1e-5);
+            assert!(weights.iter().all(|&w| w > 0.0 && w <= STRONGEST_WEIGHT));
+        }
+    }
+
+    #[test]
+    fn suggested_weights_follow_a_weighting_for_a_flexible_string() {
+        // Nylon is so flexible that the stiffness term barely matters, leaving A-weighting
+        let spec = StringSpec::nylon(0.028, 57);
+        let weights = spec.suggested_weights(650.0);
+        let f0 = spec.frequency();
+        let expected_ratio = a_weighting(3.0 * f0) / a_weighting(2.0 * f0);
+        assert!((weights[1] / weights[0] - expected_ratio).abs() < 1e-2);
+    }
+}
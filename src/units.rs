@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+pub(crate) const MM_PER_INCH: f32 = 25.4;
+
+/// Unit positions are displayed in. Everything is stored and computed in mm.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) enum LengthUnit {
+    Millimeters,
+    Inches,
+}
+
+impl LengthUnit {
+    pub(crate) const ALL: [LengthUnit; 2] = [LengthUnit::Millimeters, LengthUnit::Inches];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            LengthUnit::Millimeters => "mm",
+            LengthUnit::Inches => "in",
+        }
+    }
+
+    /// `mm` expressed in this unit
+    pub(crate) fn convert(&self, mm: f32) -> f32 {
+        match self {
+            LengthUnit::Millimeters => mm,
+            LengthUnit::Inches => mm / MM_PER_INCH,
+        }
+    }
+
+    /// A value in this unit expressed in mm
+    pub(crate) fn in_mm(&self, value: f32) -> f32 {
+        match self {
+            LengthUnit::Millimeters => value,
+            LengthUnit::Inches => value * MM_PER_INCH,
+        }
+    }
+
+    /// `mm` converted, with the precision that suits the unit and the unit appended
+    pub(crate) fn format(&self, mm: f32) -> String {
+        format!("{} {}", self.format_number(mm), self.label())
+    }
+
+    /// Like `format`, with an explicit sign for shifts and offsets
+    pub(crate) fn format_signed(&self, mm: f32) -> String {
+        let sign = if mm >= 0.0 { "+" } else { "" };
+        format!("{}{}", sign, self.format(mm))
+    }
+
+    /// `mm` converted, with the precision that suits the unit but no unit, for tables
+    pub(crate) fn format_number(&self, mm: f32) -> String {
+        match self {
+            LengthUnit::Millimeters => format!("{:.2}", mm),
+            LengthUnit::Inches => format!("{:.3}", self.convert(mm)),
+        }
+    }
+}
+
+/// Parse a length such as "25.5in", `25.5"`, "648mm", "64.8 cm" or a bare number, which is
+/// taken to be in `default`. Returns mm, or None for anything that isn't a finite length >= 0.
+pub(crate) fn parse_length(text: &str, default: LengthUnit) -> Option<f32> {
+    let text = text.trim().to_ascii_lowercase();
+    let number_end = text
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | ',' | '-' | '+' | 'e')))
+        .unwrap_or(text.len());
+    let (number, suffix) = text.split_at(number_end);
+    let value: f32 = number.replace(',', ".").parse().ok()?;
+
+    let mm = match suffix.trim() {
+        "" => default.in_mm(value),
+        "mm" => value,
+        "cm" => value * 10.0,
+        "m" => value * 1000.0,
+        "in" | "inch" | "inches" | "\"" => value * MM_PER_INCH,
+        _ => return None,
+    };
+    (mm.is_finite() && mm >= 0.0).then_some(mm)
+}
+
+/// Drag value for a length stored in mm, shown in `unit` and accepting typed input in any
+/// unit `parse_length` understands
+pub(crate) fn length_input(value: &mut f32, unit: LengthUnit) -> egui::DragValue<'_> {
+    egui::DragValue::new(value)
+        .speed(0.5)
+        .custom_formatter(move |mm, _| unit.format(mm as f32))
+        .custom_parser(move |text| parse_length(text, unit).map(f64::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_parses(text: &str, default: LengthUnit, expected_mm: f32) {
+        let mm = parse_length(text, default).unwrap_or_else(|| panic!("{:?} should parse", text));
+        assert!(
+            (mm - expected_mm).abs() < 1e-3,
+            "{:?} parsed as {} mm, expected {} mm",
+            text,
+            mm,
+            expected_mm
+        );
+    }
+
+    #[test]
+    fn parses_explicit_units() {
+        assert_parses("25.5in", LengthUnit::Millimeters, 647.7);
+        assert_parses("25.5\"", LengthUnit::Millimeters, 647.7);
+        assert_parses("25.5 inches", LengthUnit::Millimeters, 647.7);
+        assert_parses("648mm", LengthUnit::Inches, 648.0);
+        assert_parses("64.8 cm", LengthUnit::Inches, 648.0);
+        assert_parses("0.648 m", LengthUnit::Inches, 648.0);
+        assert_parses("  648 MM ", LengthUnit::Inches, 648.0);
+    }
+
+    #[test]
+    fn bare_numbers_use_the_default_unit() {
+        assert_parses("648", LengthUnit::Millimeters, 648.0);
+        assert_parses("25.5", LengthUnit::Inches, 647.7);
+    }
+
+    #[test]
+    fn accepts_comma_decimals_and_exponents() {
+        assert_parses("64,8 cm", LengthUnit::Millimeters, 648.0);
+        assert_parses("25,5in", LengthUnit::Millimeters, 647.7);
+        assert_parses("6.48e2", LengthUnit::Millimeters, 648.0);
+        assert_parses("+648mm", LengthUnit::Millimeters, 648.0);
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        for text in [
+            "", "mm", "abc", "-5", "-5mm", "648 ft", "6.4.8", "1e", "1e40 m",
+        ] {
+            assert_eq!(
+                parse_length(text, LengthUnit::Millimeters),
+                None,
+                "{:?} should be rejected",
+                text
+            );
+        }
+    }
+}
@@ -1,85 +1,307 @@
-use egui::{Color32, Pos2, Stroke, Vec2};
+use egui::{Color32, Pos2, Stroke};
+use serde::{Deserialize, Serialize};
+
+use crate::calculation::{FretMode, OptimalPositions};
+use crate::color::ColorExt;
+
+use crate::app::{HarmonicApp, VisualizerView};
+use crate::blend::blend_response;
+use crate::response::{DB_FLOOR, ResponsePoint, pickup_response};
+
+const MARKER_COLOR: Color32 = Color32::from_rgb(255, 210, 90);
+const NODE_COLOR: Color32 = Color32::from_rgb(224, 122, 95);
+const RESPONSE_PLOT_SPLIT: f32 = 0.65; // Fraction of the width left for the string view
+const RESPONSE_HARMONICS: u8 = 20;
+const BLEND_HARMONICS: u8 = 12;
+
+// Fixed chrome shared by every view; whatever space is left scales with the window
+const SIDE_MARGIN: f32 = 20.0;
+const TOP_PADDING: f32 = 50.0; // Room above the plots for titles and pickup labels
+const LABEL_HEIGHT: f32 = 10.0; // Gap between a title and the plot below it
+const BOTTOM_PADDING: f32 = 10.0;
+const AXIS_LABEL_SPACE: f32 = 20.0; // Room for the bridge/nut labels under a plot
+const RULER_HEIGHT: f32 = 44.0; // Length and percentage axes under the harmonic rows
+const PERCENT_AXIS_OFFSET: f32 = 22.0; // Percentage axis sits this far below the length axis
+const FRACTION_DENOMINATORS: std::ops::RangeInclusive<u8> = 2..=8; // Marks at L/2 ... L/8
+const ZOOM_PER_SCROLL_POINT: f32 = 0.002;
+
+// Single-string view
+const ROW_LABEL_WIDTH: f32 = 28.0; // Left gutter for the H{n} labels
+const WEIGHT_LABEL_WIDTH: f32 = 44.0; // Right gutter for the weights
+const GAP_AFTER_HEAT_MAP: f32 = 25.0; // Doubles as the drag handle between heat map and rows
+const NUM_HARMONICS: usize = 6; // Harmonics 2-7
+const MIN_HEAT_MAP_HEIGHT: f32 = 20.0;
+const MIN_HARMONIC_SPACING: f32 = 14.0;
+
+/// How the single-string view divides its height between the heat map and the harmonic rows.
+/// Kept as a fraction so the split survives window resizes.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct VisualizerLayout {
+    pub(crate) heat_map_share: f32,
+}
 
-use crate::calculation::get_anti_nodes_for_harmonic;
-use crate::color::{ColorExt, HEATMAP_COLORS, heat_to_color};
+impl Default for VisualizerLayout {
+    fn default() -> Self {
+        Self {
+            heat_map_share: 0.3,
+        }
+    }
+}
 
-use crate::app::HarmonicApp;
+/// Visible part of the string in the single-string view, as fractions of the scale length
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct ZoomRange {
+    pub(crate) start: f32,
+    pub(crate) end: f32,
+}
 
-impl HarmonicApp {
-    pub(crate) fn calculate_visualizer_height(&self) -> f32 {
-        // These constants must match the values used in draw_visualization()
-        const TOP_PADDING: f32 = 50.0; // Distance from rect.min.y to heat map
-        const LABEL_HEIGHT: f32 = 10.0; // Space above heat map for label
-        const HEAT_MAP_HEIGHT: f32 = 60.0;
-        const GAP_AFTER_HEAT_MAP: f32 = 25.0;
-        const HARMONIC_SPACING: f32 = 22.0;
-        const NUM_HARMONICS: usize = 6; // Harmonics 2-7
-        const BOTTOM_PADDING: f32 = 10.0; // Space for bridge/nut labels
-
-        TOP_PADDING - LABEL_HEIGHT
-            + HEAT_MAP_HEIGHT
-            + GAP_AFTER_HEAT_MAP
-            + (NUM_HARMONICS as f32 * HARMONIC_SPACING)
-            + BOTTOM_PADDING
-    }
-
-    pub(crate) fn draw_visualization(&self, ui: &mut egui::Ui) {
-        // Constants - must match calculate_visualizer_height()
-        const SIDE_MARGIN: f32 = 20.0;
-        const TOP_PADDING: f32 = 50.0;
-        const LABEL_HEIGHT: f32 = 10.0;
-        const HEAT_MAP_HEIGHT: f32 = 60.0;
-        const GAP_AFTER_HEAT_MAP: f32 = 25.0;
-        const HARMONIC_SPACING: f32 = 22.0;
-        const BOTTOM_PADDING: f32 = 10.0;
+impl Default for ZoomRange {
+    fn default() -> Self {
+        Self {
+            start: 0.0,
+            end: 1.0,
+        }
+    }
+}
 
-        let available_width = ui.available_width() - (SIDE_MARGIN * 2.0);
-        let viz_height = self.calculate_visualizer_height();
+impl ZoomRange {
+    /// Narrowest view, as a fraction of the scale length
+    const MIN_SPAN: f32 = 0.01;
 
-        let (response, painter) = ui.allocate_painter(
-            Vec2::new(available_width + (SIDE_MARGIN * 2.0), viz_height),
-            egui::Sense::hover(),
-        );
+    pub(crate) fn is_zoomed(&self) -> bool {
+        *self != Self::default()
+    }
 
-        let rect = response.rect;
-        let string_start_x = rect.min.x + SIDE_MARGIN;
-        let string_end_x = rect.max.x - SIDE_MARGIN;
-        let string_width = string_end_x - string_start_x;
+    fn span(&self) -> f32 {
+        self.end - self.start
+    }
 
-        // Draw background
-        painter.rect_filled(rect, 0.0, Color32::from_gray(20));
+    /// Visible range in mm for a string of `length`
+    pub(crate) fn range_mm(&self, length: f32) -> (f32, f32) {
+        (self.start * length, self.end * length)
+    }
 
-        // Calculate heat map
-        let heat_map = self.calculate_heat_map();
-        let max_heat = heat_map.iter().cloned().fold(0.0_f32, f32::max);
+    /// Scale the span by `factor`, keeping the point `anchor` of the way across the view fixed
+    fn zoom(&mut self, factor: f32, anchor: f32) {
+        let pivot = self.start + anchor * self.span();
+        let span = (self.span() * factor).clamp(Self::MIN_SPAN, 1.0);
+        self.start = pivot - anchor * span;
+        self.end = self.start + span;
+        self.pan(0.0);
+    }
 
-        // Draw heat map
-        let heat_map_y = rect.min.y + TOP_PADDING;
+    /// Shift by `delta` of the scale length, stopping at the bridge and the nut
+    fn pan(&mut self, delta: f32) {
+        let span = self.span();
+        self.start = (self.start + delta).clamp(0.0, 1.0 - span);
+        self.end = self.start + span;
+    }
+}
 
-        // Draw the heat map as a series of rectangles that exactly cover the space
-        // Calculate the exact width needed to avoid gaps
-        let heat_map_rect = egui::Rect::from_min_max(
-            Pos2::new(string_start_x, heat_map_y),
-            Pos2::new(string_end_x, heat_map_y + HEAT_MAP_HEIGHT),
+/// Screen positions of the single-string view for one frame
+struct SingleStringRects {
+    string_start_x: f32,
+    string_end_x: f32,
+    heat_map: egui::Rect,
+    splitter: egui::Rect,
+    rows_top: f32,
+    harmonic_spacing: f32,
+}
+
+impl SingleStringRects {
+    fn row_y(&self, row: usize) -> f32 {
+        self.rows_top + (row as f32 + 0.5) * self.harmonic_spacing
+    }
+}
+
+impl VisualizerLayout {
+    /// Height shared by the heat map and the rows, i.e. everything but the fixed chrome
+    fn flexible_height(rect: egui::Rect) -> f32 {
+        (rect.height()
+            - TOP_PADDING
+            - GAP_AFTER_HEAT_MAP
+            - RULER_HEIGHT
+            - AXIS_LABEL_SPACE
+            - BOTTOM_PADDING)
+            .max(MIN_HEAT_MAP_HEIGHT + NUM_HARMONICS as f32 * MIN_HARMONIC_SPACING)
+    }
+
+    fn single_string(&self, rect: egui::Rect) -> SingleStringRects {
+        let string_start_x = rect.min.x + SIDE_MARGIN + ROW_LABEL_WIDTH;
+        let string_end_x = rect.max.x - SIDE_MARGIN - WEIGHT_LABEL_WIDTH;
+
+        let flexible = Self::flexible_height(rect);
+        let heat_map_height = self.clamped_share(flexible) * flexible;
+        let heat_map = egui::Rect::from_min_max(
+            Pos2::new(string_start_x, rect.min.y + TOP_PADDING),
+            Pos2::new(string_end_x, rect.min.y + TOP_PADDING + heat_map_height),
+        );
+        let rows_top = heat_map.bottom() + GAP_AFTER_HEAT_MAP;
+        let splitter = egui::Rect::from_min_max(
+            Pos2::new(string_start_x, heat_map.bottom() + 4.0),
+            Pos2::new(string_end_x, rows_top - 4.0),
         );
 
-        // Draw each segment as a rectangle spanning the full width
-        for (i, &heat) in heat_map.iter().enumerate() {
-            let normalized_heat = if max_heat > 0.0 { heat / max_heat } else { 0.0 };
-            let color = heat_to_color(normalized_heat, &HEATMAP_COLORS);
+        SingleStringRects {
+            string_start_x,
+            string_end_x,
+            heat_map,
+            splitter,
+            rows_top,
+            harmonic_spacing: (flexible - heat_map_height) / NUM_HARMONICS as f32,
+        }
+    }
+
+    fn clamped_share(&self, flexible: f32) -> f32 {
+        let min = MIN_HEAT_MAP_HEIGHT / flexible;
+        let max = 1.0 - NUM_HARMONICS as f32 * MIN_HARMONIC_SPACING / flexible;
+        self.heat_map_share.clamp(min, max.max(min))
+    }
+
+    /// Move the boundary between heat map and rows to follow a splitter dragged to `y`
+    fn drag_splitter(&mut self, rect: egui::Rect, y: f32) {
+        let flexible = Self::flexible_height(rect);
+        let heat_map_height = y - GAP_AFTER_HEAT_MAP / 2.0 - (rect.min.y + TOP_PADDING);
+        self.heat_map_share = heat_map_height / flexible;
+        self.heat_map_share = self.clamped_share(flexible);
+    }
+}
+
+impl HarmonicApp {
+    pub(crate) fn draw_visualization(&mut self, ui: &mut egui::Ui) {
+        let (response, painter) =
+            ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+
+        // Draw background
+        painter.rect_filled(response.rect, 0.0, Color32::from_gray(20));
+
+        // Give the frequency response plot the right-hand part of the area
+        let rect = if self.show_response {
+            let (rect, response_rect) = response
+                .rect
+                .split_left_right_at_fraction(RESPONSE_PLOT_SPLIT);
+            self.draw_response_plot(&painter, response_rect);
+            rect
+        } else {
+            response.rect
+        };
+        match self.view {
+            VisualizerView::StringSet => return self.draw_string_set(&painter, rect),
+            VisualizerView::ScaleSweep => return self.draw_scale_sweep(&painter, rect),
+            VisualizerView::Blend => return self.draw_blend(&painter, rect),
+            VisualizerView::SingleString => {}
+        }
 
-            // Calculate segment boundaries
-            let segment_start = i as f32 / heat_map.len() as f32;
-            let segment_end = (i + 1) as f32 / heat_map.len() as f32;
+        // Dragging the gap under the heat map trades its height against the harmonic rows;
+        // a double click restores the default split
+        let splitter_rect = self.layout.single_string(rect).splitter;
+        let splitter = ui
+            .interact(
+                splitter_rect,
+                ui.id().with("heat_map_splitter"),
+                egui::Sense::click_and_drag(),
+            )
+            .on_hover_cursor(egui::CursorIcon::ResizeVertical);
+        if splitter.double_clicked() {
+            self.layout = VisualizerLayout::default();
+        } else if splitter.dragged()
+            && let Some(pointer) = splitter.interact_pointer_pos()
+        {
+            self.layout.drag_splitter(rect, pointer.y);
+        }
 
-            let x_start = (heat_map_rect.left() + segment_start * heat_map_rect.width()).round();
-            let x_end = (heat_map_rect.left() + segment_end * heat_map_rect.width()).round();
+        // Labels sit in gutters outside the string so they never overlap or clip
+        let layout = self.layout.single_string(rect);
+        let string_start_x = layout.string_start_x;
+        let string_end_x = layout.string_end_x;
+        let string_width = string_end_x - string_start_x;
 
-            let segment_rect = egui::Rect::from_min_max(
-                Pos2::new(x_start, heat_map_y),
-                Pos2::new(x_end, heat_map_y + HEAT_MAP_HEIGHT),
+        if splitter.hovered() || splitter.dragged() {
+            painter.line_segment(
+                [
+                    Pos2::new(string_start_x, layout.splitter.center().y),
+                    Pos2::new(string_end_x, layout.splitter.center().y),
+                ],
+                Stroke::new(1.0, Color32::from_gray(90)),
             );
-            painter.rect_filled(segment_rect, 0.0, color);
+        }
+
+        // Mouse wheel (or pinch) zooms around the pointer, dragging pans
+        let zoom_before = self.zoom;
+        if let Some(pointer) = response.hover_pos()
+            && rect.contains(pointer)
+        {
+            let (scroll, pinch) = ui.input(|i| (i.smooth_scroll_delta.y, i.zoom_delta()));
+            let factor = (-scroll * ZOOM_PER_SCROLL_POINT).exp() / pinch;
+            if factor != 1.0 {
+                let anchor = ((pointer.x - string_start_x) / string_width).clamp(0.0, 1.0);
+                self.zoom.zoom(factor, anchor);
+            }
+        }
+        if response.dragged_by(egui::PointerButton::Primary) {
+            self.zoom
+                .pan(-response.drag_delta().x / string_width * self.zoom.span());
+        }
+        if self.zoom != zoom_before {
+            ui.ctx().request_repaint();
+        }
+
+        let zoom = self.zoom;
+        let length = self.string_length;
+        let pos_to_x =
+            |pos: f32| string_start_x + ((pos / length - zoom.start) / zoom.span()) * string_width;
+
+        // Left click places a marker on the string, right click removes it
+        if let Some(pointer) = response.interact_pointer_pos()
+            && rect.contains(pointer)
+        {
+            if response.clicked() {
+                let fraction = ((pointer.x - string_start_x) / string_width).clamp(0.0, 1.0);
+                self.marker_position = Some((zoom.start + fraction * zoom.span()) * length);
+            } else if response.secondary_clicked() {
+                self.marker_position = None;
+            }
+        }
+
+        // Draw heat map
+        let heat_map_rect = layout.heat_map;
+        let heat_map_y = heat_map_rect.top();
+        self.heat_texture.paint(&painter, heat_map_rect);
+
+        // Everything placed along the string is clipped to it, so zoomed-out-of-view
+        // positions don't spill into the label gutters
+        let string_painter = painter.with_clip_rect(egui::Rect::from_x_y_ranges(
+            string_start_x - 1.0..=string_end_x + 1.0,
+            rect.y_range(),
+        ));
+
+        // Simple fractions of the scale measured from the bridge, labelled where there's room
+        let mut last_label_x = f32::NEG_INFINITY;
+        for denominator in FRACTION_DENOMINATORS {
+            let x = pos_to_x(length / denominator as f32);
+            string_painter.line_segment(
+                [
+                    Pos2::new(x, heat_map_rect.top()),
+                    Pos2::new(x, heat_map_rect.bottom()),
+                ],
+                Stroke::new(1.0, Color32::from_white_alpha(70)),
+            );
+            if (last_label_x - x).abs() >= 22.0 {
+                let galley = string_painter.layout_no_wrap(
+                    format!("1/{}", denominator),
+                    egui::FontId::proportional(9.0),
+                    Color32::WHITE,
+                );
+                let label_rect = egui::Align2::CENTER_TOP
+                    .anchor_size(Pos2::new(x, heat_map_rect.top() + 1.0), galley.size());
+                string_painter.rect_filled(
+                    label_rect.expand(1.0),
+                    2.0,
+                    Color32::from_black_alpha(120),
+                );
+                string_painter.galley(label_rect.min, galley, Color32::WHITE);
+                last_label_x = x;
+            }
         }
 
         // Draw heat map label
@@ -91,15 +313,25 @@ impl HarmonicApp {
             Color32::WHITE,
         );
 
-        // Draw individual harmonics
-        let mut current_y = heat_map_y + HEAT_MAP_HEIGHT + GAP_AFTER_HEAT_MAP;
+        // Draw individual harmonics for the note being played
+        let last_row_y = layout.row_y(NUM_HARMONICS - 1);
+        let model = self.score_model();
+        let vibrating_length = model.vibrating_length();
+        let bridge_color = Color32::parse_hex(0xB57EDC);
+        let neck_color = Color32::parse_hex(0xB266FF);
+        let pickup_responses = [
+            self.optimal_positions.bridge_position,
+            self.optimal_positions.neck_position,
+        ]
+        .map(|pos| (pos, model.responses(vibrating_length, pos)));
 
-        for harmonic in 2..=7_u8 {
-            let anti_nodes = get_anti_nodes_for_harmonic(self.string_length, harmonic);
+        for (i, harmonic) in (2..=7_u8).enumerate() {
+            let weight = self.weights[i];
+            let anti_nodes = model.anti_nodes(harmonic);
 
             // Draw string line
-            let string_y = current_y;
-            painter.line_segment(
+            let string_y = layout.row_y(i);
+            string_painter.line_segment(
                 [
                     Pos2::new(string_start_x, string_y),
                     Pos2::new(string_end_x, string_y),
@@ -107,53 +339,90 @@ impl HarmonicApp {
                 Stroke::new(1.5, Color32::GRAY),
             );
 
-            // Draw anti-nodes (all with consistent opacity)
+            // Draw anti-nodes, larger and more opaque the more the harmonic is weighted.
+            // Harmonics weighted to be suppressed get a warmer color.
+            let strength = (weight.abs() / 2.0).min(1.0);
+            let base_color = if weight < 0.0 {
+                Color32::parse_hex(0xCF8FA1)
+            } else {
+                Color32::parse_hex(0xA6CFA1)
+            };
+            let color = base_color.gamma_multiply(0.25 + 0.75 * strength);
             for anti_node in anti_nodes {
-                let x = string_start_x + (anti_node / self.string_length) * string_width;
-                // Use consistent opacity for all anti-nodes, regardless of weight
-                let color = Color32::parse_hex(0xA6CFA1);
-
-                painter.circle_filled(Pos2::new(x, string_y), 4.0, color);
-                // painter.circle_stroke(Pos2::new(x, string_y), 4.0, Stroke::new(1.0, Color32::WHITE));
+                let x = pos_to_x(anti_node);
+                string_painter.circle_filled(Pos2::new(x, string_y), 2.0 + 3.0 * strength, color);
             }
 
-            // // Draw label (inside the bounds to prevent clipping)
-            // painter.text(
-            //     Pos2::new(string_start_x + 5.0, string_y),
-            //     egui::Align2::LEFT_CENTER,
-            //     format!("H{}", harmonic),
-            //     egui::FontId::proportional(11.0),
-            //     Color32::from_gray(200),
-            // );
+            // Draw nodes as short ticks across the string
+            for node in model.nodes(harmonic) {
+                let x = pos_to_x(node);
+                string_painter.line_segment(
+                    [Pos2::new(x, string_y - 5.0), Pos2::new(x, string_y + 5.0)],
+                    Stroke::new(1.5, NODE_COLOR),
+                );
+            }
 
-            // // Draw weight indicator (inside the bounds to prevent clipping)
-            // painter.text(
-            //     Pos2::new(string_end_x - 5.0, string_y),
-            //     egui::Align2::RIGHT_CENTER,
-            //     format!("w: {:.2}", weight),
-            //     egui::FontId::proportional(9.0),
-            //     Color32::from_gray(150),
-            // );
+            // Contribution of this harmonic to each pickup's score: up for positive, down
+            // for negative, full height at a weight of 2 on an anti-node
+            for ((pos, responses), color) in pickup_responses.iter().zip([bridge_color, neck_color])
+            {
+                let x = pos_to_x(*pos);
+                let contribution = (weight * responses[i] / 2.0).clamp(-1.0, 1.0);
+                let bar_height = contribution * (layout.harmonic_spacing / 2.0 - 2.0);
+                string_painter.rect_filled(
+                    egui::Rect::from_two_pos(
+                        Pos2::new(x + 2.0, string_y),
+                        Pos2::new(x + 6.0, string_y - bar_height),
+                    ),
+                    0.0,
+                    color,
+                );
+            }
 
-            current_y += HARMONIC_SPACING;
+            painter.text(
+                Pos2::new(string_start_x - 6.0, string_y),
+                egui::Align2::RIGHT_CENTER,
+                format!("H{}", harmonic),
+                egui::FontId::proportional(11.0),
+                Color32::from_gray(200),
+            );
+            painter.text(
+                Pos2::new(string_end_x + 6.0, string_y),
+                egui::Align2::LEFT_CENTER,
+                format!("w {:.2}", weight),
+                egui::FontId::proportional(10.0),
+                Color32::from_gray(150),
+            );
         }
 
-        let bridge_color = Color32::parse_hex(0xB57EDC);
-        let neck_color = Color32::parse_hex(0xB266FF);
+        // Mark where the string is stopped
+        if let FretMode::Fretted(fret) = self.fret_mode {
+            let fret_x = pos_to_x(vibrating_length - model.compensation);
+            string_painter.line_segment(
+                [Pos2::new(fret_x, heat_map_y), Pos2::new(fret_x, last_row_y)],
+                Stroke::new(1.5, Color32::from_gray(200)),
+            );
+            string_painter.text(
+                Pos2::new(fret_x, heat_map_y - LABEL_HEIGHT),
+                egui::Align2::CENTER_BOTTOM,
+                format!("Fret {}", fret),
+                egui::FontId::proportional(11.0),
+                Color32::from_gray(200),
+            );
+        }
 
         // Draw bridge pickup position line
-        let bridge_x = string_start_x
-            + (self.optimal_positions.bridge_position / self.string_length) * string_width;
-        painter.line_segment(
+        let bridge_x = pos_to_x(self.optimal_positions.bridge_position);
+        string_painter.line_segment(
             [
                 Pos2::new(bridge_x, heat_map_y),
-                Pos2::new(bridge_x, current_y - HARMONIC_SPACING),
+                Pos2::new(bridge_x, last_row_y),
             ],
             Stroke::new(2.0, bridge_color),
         );
 
         // Draw bridge pickup label
-        painter.text(
+        string_painter.text(
             Pos2::new(bridge_x, heat_map_y - LABEL_HEIGHT - 15.0),
             egui::Align2::CENTER_BOTTOM,
             "Bridge",
@@ -162,18 +431,14 @@ impl HarmonicApp {
         );
 
         // Draw neck pickup position line
-        let neck_x = string_start_x
-            + (self.optimal_positions.neck_position / self.string_length) * string_width;
-        painter.line_segment(
-            [
-                Pos2::new(neck_x, heat_map_y),
-                Pos2::new(neck_x, current_y - HARMONIC_SPACING),
-            ],
+        let neck_x = pos_to_x(self.optimal_positions.neck_position);
+        string_painter.line_segment(
+            [Pos2::new(neck_x, heat_map_y), Pos2::new(neck_x, last_row_y)],
             Stroke::new(2.0, neck_color),
         );
 
         // Draw neck pickup label
-        painter.text(
+        string_painter.text(
             Pos2::new(neck_x, heat_map_y - LABEL_HEIGHT - 15.0),
             egui::Align2::CENTER_BOTTOM,
             "Neck",
@@ -181,9 +446,361 @@ impl HarmonicApp {
             neck_color,
         );
 
+        // Draw humbucker outlines spanning both coils
+        if self.humbucker_enabled {
+            for (placement, color) in [
+                (self.humbucker_positions.bridge, bridge_color),
+                (self.humbucker_positions.neck, neck_color),
+            ] {
+                let [first, second] = placement.coils.map(pos_to_x);
+                string_painter.rect_stroke(
+                    egui::Rect::from_min_max(
+                        Pos2::new(first, heat_map_y + 4.0),
+                        Pos2::new(second, heat_map_rect.bottom() - 4.0),
+                    ),
+                    2.0,
+                    Stroke::new(1.5, color),
+                    egui::StrokeKind::Middle,
+                );
+                for x in [first, second] {
+                    string_painter.circle_stroke(
+                        Pos2::new(x, heat_map_rect.center().y),
+                        3.0,
+                        Stroke::new(1.5, color),
+                    );
+                }
+            }
+        }
+
+        // Draw user marker
+        if let Some(marker) = self.marker_position {
+            let marker_x = pos_to_x(marker);
+            string_painter.line_segment(
+                [
+                    Pos2::new(marker_x, heat_map_y),
+                    Pos2::new(marker_x, last_row_y),
+                ],
+                Stroke::new(1.5, MARKER_COLOR),
+            );
+            string_painter.text(
+                Pos2::new(marker_x, heat_map_rect.bottom() + 2.0),
+                egui::Align2::CENTER_TOP,
+                "Marker",
+                egui::FontId::proportional(10.0),
+                MARKER_COLOR,
+            );
+        }
+
+        // Length axis in the active unit with a percentage-of-scale axis under it
+        let axis_y = last_row_y + layout.harmonic_spacing / 2.0;
+        let (start_mm, end_mm) = zoom.range_mm(length);
+        draw_axis(
+            &painter,
+            string_start_x..=string_end_x,
+            axis_y,
+            (self.unit.convert(start_mm), self.unit.convert(end_mm)),
+            self.unit.label(),
+        );
+        draw_axis(
+            &painter,
+            string_start_x..=string_end_x,
+            axis_y + PERCENT_AXIS_OFFSET,
+            (zoom.start * 100.0, zoom.end * 100.0),
+            "%",
+        );
+
+        // Draw bridge and nut labels when they're in view
+        for (pos, label) in [(0.0, "Bridge"), (length, "Nut")] {
+            let x = pos_to_x(pos);
+            if (string_start_x - 1.0..=string_end_x + 1.0).contains(&x) {
+                painter.text(
+                    Pos2::new(x, rect.max.y - BOTTOM_PADDING),
+                    egui::Align2::CENTER_BOTTOM,
+                    label,
+                    egui::FontId::proportional(12.0),
+                    Color32::WHITE,
+                );
+            }
+        }
+    }
+
+    fn draw_response_plot(&self, painter: &egui::Painter, rect: egui::Rect) {
+        const MARGIN: f32 = 30.0;
+        const TOP_PADDING: f32 = 40.0;
+        const LABEL_HEIGHT: f32 = 10.0;
+
+        let plot_rect = egui::Rect::from_min_max(
+            Pos2::new(rect.min.x + MARGIN, rect.min.y + TOP_PADDING),
+            Pos2::new(rect.max.x - 10.0, rect.max.y - MARGIN),
+        );
+        painter.rect_filled(plot_rect, 0.0, Color32::from_gray(28));
+
+        painter.text(
+            Pos2::new(plot_rect.left(), plot_rect.top() - LABEL_HEIGHT),
+            egui::Align2::LEFT_BOTTOM,
+            format!("Pickup Response (f0 = {:.1} Hz)", self.tuning_frequency),
+            egui::FontId::proportional(12.0),
+            Color32::WHITE,
+        );
+
+        let model = self.score_model();
+        let mut series = vec![
+            (
+                self.optimal_positions.bridge_position,
+                Color32::parse_hex(0xB57EDC),
+            ),
+            (
+                self.optimal_positions.neck_position,
+                Color32::parse_hex(0xB266FF),
+            ),
+        ];
+        if let Some(marker) = self.marker_position {
+            series.push((marker, MARKER_COLOR));
+        }
+        let responses: Vec<(Vec<ResponsePoint>, Color32)> = series
+            .into_iter()
+            .map(|(pos, color)| {
+                (
+                    pickup_response(&model, pos, self.tuning_frequency, RESPONSE_HARMONICS),
+                    color,
+                )
+            })
+            .collect();
+
+        let max_frequency = responses
+            .iter()
+            .flat_map(|(points, _)| points.iter().map(|p| p.frequency))
+            .fold(0.0_f32, f32::max)
+            * 1.05;
+        if max_frequency <= 0.0 {
+            return;
+        }
+        let to_screen = |point: &ResponsePoint| {
+            Pos2::new(
+                plot_rect.left() + point.frequency / max_frequency * plot_rect.width(),
+                plot_rect.top() + point.db / DB_FLOOR * plot_rect.height(),
+            )
+        };
+
+        // dB grid
+        for db in (0..=60).step_by(20) {
+            let y = plot_rect.top() + (-(db as f32)) / DB_FLOOR * plot_rect.height();
+            painter.line_segment(
+                [
+                    Pos2::new(plot_rect.left(), y),
+                    Pos2::new(plot_rect.right(), y),
+                ],
+                Stroke::new(1.0, Color32::from_gray(50)),
+            );
+            painter.text(
+                Pos2::new(plot_rect.left() - 3.0, y),
+                egui::Align2::RIGHT_CENTER,
+                format!("-{}", db),
+                egui::FontId::proportional(9.0),
+                Color32::from_gray(160),
+            );
+        }
+
+        for (points, color) in &responses {
+            let screen: Vec<Pos2> = points.iter().map(to_screen).collect();
+            for &point in &screen {
+                painter.circle_filled(point, 2.5, *color);
+            }
+            painter.add(egui::Shape::line(screen, Stroke::new(1.5, *color)));
+        }
+
+        painter.text(
+            Pos2::new(plot_rect.left(), plot_rect.bottom() + 3.0),
+            egui::Align2::LEFT_TOP,
+            "0 Hz",
+            egui::FontId::proportional(9.0),
+            Color32::from_gray(160),
+        );
+        painter.text(
+            Pos2::new(plot_rect.right(), plot_rect.bottom() + 3.0),
+            egui::Align2::RIGHT_TOP,
+            format!("{:.0} Hz", max_frequency),
+            egui::FontId::proportional(9.0),
+            Color32::from_gray(160),
+        );
+        painter.text(
+            Pos2::new(plot_rect.left() - 3.0, plot_rect.top() - 2.0),
+            egui::Align2::RIGHT_BOTTOM,
+            "dB",
+            egui::FontId::proportional(9.0),
+            Color32::from_gray(160),
+        );
+    }
+
+    fn draw_blend(&self, painter: &egui::Painter, rect: egui::Rect) {
+        const HEAT_MAP_HEIGHT: f32 = 40.0;
+        const GAP_AFTER_HEAT_MAP: f32 = 30.0;
+
+        let in_phase_color = Color32::parse_hex(0x7EB6FF);
+        let reversed_color = Color32::parse_hex(0xFF7E7E);
+
+        let heat_map_rect = egui::Rect::from_min_max(
+            Pos2::new(rect.min.x + SIDE_MARGIN, rect.min.y + TOP_PADDING),
+            Pos2::new(
+                rect.max.x - SIDE_MARGIN,
+                rect.min.y + TOP_PADDING + HEAT_MAP_HEIGHT,
+            ),
+        );
+        self.blend_texture.paint(painter, heat_map_rect);
+
+        painter.text(
+            Pos2::new(heat_map_rect.left(), heat_map_rect.top() - LABEL_HEIGHT),
+            egui::Align2::LEFT_BOTTOM,
+            "Blend Heat Map (sweeping pickup 1)",
+            egui::FontId::proportional(12.0),
+            Color32::WHITE,
+        );
+
+        // Pickup positions, coloured by phase
+        for (i, pickup) in self.blend_pickups.iter().enumerate() {
+            let x = heat_map_rect.left()
+                + (pickup.position / self.string_length) * heat_map_rect.width();
+            let color = if pickup.reversed {
+                reversed_color
+            } else {
+                in_phase_color
+            };
+            painter.line_segment(
+                [
+                    Pos2::new(x, heat_map_rect.top()),
+                    Pos2::new(x, heat_map_rect.bottom()),
+                ],
+                Stroke::new(2.0, color),
+            );
+            painter.text(
+                Pos2::new(x, heat_map_rect.bottom() + 2.0),
+                egui::Align2::CENTER_TOP,
+                format!("{}", i + 1),
+                egui::FontId::proportional(10.0),
+                color,
+            );
+        }
+
+        // Combined per-harmonic response as signed bars around a zero line
+        let chart_rect = egui::Rect::from_min_max(
+            Pos2::new(
+                heat_map_rect.left(),
+                heat_map_rect.bottom() + GAP_AFTER_HEAT_MAP,
+            ),
+            Pos2::new(heat_map_rect.right(), rect.max.y - AXIS_LABEL_SPACE),
+        );
+        let response = blend_response(&self.score_model(), &self.blend_pickups, BLEND_HARMONICS);
+        let full_scale = self
+            .blend_pickups
+            .iter()
+            .map(|pickup| pickup.level)
+            .sum::<f32>()
+            .max(f32::EPSILON);
+        let zero_y = chart_rect.center().y;
+        let slot_width = chart_rect.width() / BLEND_HARMONICS as f32;
+
+        painter.text(
+            Pos2::new(chart_rect.left(), chart_rect.top() - 4.0),
+            egui::Align2::LEFT_BOTTOM,
+            "Combined Harmonic Amplitude",
+            egui::FontId::proportional(11.0),
+            Color32::WHITE,
+        );
+        painter.line_segment(
+            [
+                Pos2::new(chart_rect.left(), zero_y),
+                Pos2::new(chart_rect.right(), zero_y),
+            ],
+            Stroke::new(1.0, Color32::from_gray(120)),
+        );
+
+        for (i, &amplitude) in response.iter().enumerate() {
+            let center_x = chart_rect.left() + (i as f32 + 0.5) * slot_width;
+            let bar_height = amplitude / full_scale * chart_rect.height() / 2.0;
+            let color = if amplitude >= 0.0 {
+                in_phase_color
+            } else {
+                reversed_color
+            };
+            painter.rect_filled(
+                egui::Rect::from_two_pos(
+                    Pos2::new(center_x - slot_width * 0.3, zero_y),
+                    Pos2::new(center_x + slot_width * 0.3, zero_y - bar_height),
+                ),
+                0.0,
+                color,
+            );
+            painter.text(
+                Pos2::new(center_x, chart_rect.bottom() + 2.0),
+                egui::Align2::CENTER_TOP,
+                format!("H{}", i + 1),
+                egui::FontId::proportional(9.0),
+                Color32::from_gray(180),
+            );
+        }
+    }
+
+    fn draw_string_set(&self, painter: &egui::Painter, rect: egui::Rect) {
+        let map_rect = egui::Rect::from_min_max(
+            Pos2::new(rect.min.x + SIDE_MARGIN, rect.min.y + TOP_PADDING),
+            Pos2::new(
+                rect.max.x - SIDE_MARGIN,
+                rect.max.y - BOTTOM_PADDING - AXIS_LABEL_SPACE,
+            ),
+        );
+        self.string_set_texture.paint(painter, map_rect);
+
+        painter.text(
+            Pos2::new(map_rect.left(), map_rect.top() - LABEL_HEIGHT),
+            egui::Align2::LEFT_BOTTOM,
+            "Heat Map per String (row-normalized)",
+            egui::FontId::proportional(12.0),
+            Color32::WHITE,
+        );
+
+        let max_length = self.string_set_heat_map.max_length;
+        let num_strings = self.strings.len();
+        if num_strings == 0 || max_length <= 0.0 {
+            return;
+        }
+        let row_height = map_rect.height() / num_strings as f32;
+        let pos_to_x = |pos: f32| map_rect.left() + (pos / max_length) * map_rect.width();
+        let row_center = |i: usize| map_rect.top() + (i as f32 + 0.5) * row_height;
+
+        // Row separators and string numbers
+        for i in 0..num_strings {
+            if i > 0 {
+                let y = map_rect.top() + i as f32 * row_height;
+                painter.line_segment(
+                    [
+                        Pos2::new(map_rect.left(), y),
+                        Pos2::new(map_rect.right(), y),
+                    ],
+                    Stroke::new(1.0, Color32::from_black_alpha(160)),
+                );
+            }
+            painter.text(
+                Pos2::new(map_rect.left() - 4.0, row_center(i)),
+                egui::Align2::RIGHT_CENTER,
+                format!("{}", i + 1),
+                egui::FontId::proportional(10.0),
+                Color32::from_gray(200),
+            );
+        }
+
+        // Optimum lines across the string spread
+        draw_optimum_traces(
+            painter,
+            map_rect,
+            &self.string_optima,
+            pos_to_x,
+            row_center,
+            true,
+        );
+
         // Draw bridge and nut labels
         painter.text(
-            Pos2::new(string_start_x, rect.max.y - BOTTOM_PADDING),
+            Pos2::new(map_rect.left(), rect.max.y - BOTTOM_PADDING),
             egui::Align2::CENTER_BOTTOM,
             "Bridge",
             egui::FontId::proportional(12.0),
@@ -191,43 +808,204 @@ impl HarmonicApp {
         );
 
         painter.text(
-            Pos2::new(string_end_x, rect.max.y - BOTTOM_PADDING),
-            egui::Align2::CENTER_BOTTOM,
-            "Nut",
+            Pos2::new(map_rect.right(), rect.max.y - BOTTOM_PADDING),
+            egui::Align2::RIGHT_BOTTOM,
+            format!("Nut ({})", self.unit.format(max_length)),
             egui::FontId::proportional(12.0),
             Color32::WHITE,
         );
     }
 
-    pub(crate) fn calculate_heat_map(&self) -> Vec<f32> {
-        let mut heat_map = vec![0.0; self.heat_map_resolution];
+    fn draw_scale_sweep(&self, painter: &egui::Painter, rect: egui::Rect) {
+        let map_rect = egui::Rect::from_min_max(
+            Pos2::new(rect.min.x + SIDE_MARGIN, rect.min.y + TOP_PADDING),
+            Pos2::new(
+                rect.max.x - SIDE_MARGIN,
+                rect.max.y - BOTTOM_PADDING - AXIS_LABEL_SPACE,
+            ),
+        );
+        self.scale_sweep_texture.paint(painter, map_rect);
 
-        for (i, heat) in heat_map.iter_mut().enumerate() {
-            let pos = (i as f32 / self.heat_map_resolution as f32) * self.string_length;
+        painter.text(
+            Pos2::new(map_rect.left(), map_rect.top() - LABEL_HEIGHT),
+            egui::Align2::LEFT_BOTTOM,
+            "Heat Map vs Scale Length",
+            egui::FontId::proportional(12.0),
+            Color32::WHITE,
+        );
 
-            // Calculate sine wave falloff
-            let total_heat: f32 = (2..=7_u8)
-                .zip(self.weights.iter())
-                .map(|(harmonic, &weight)| {
-                    let min_dist = get_anti_nodes_for_harmonic(self.string_length, harmonic)
-                        .into_iter()
-                        .map(|anti_node| (pos - anti_node).abs())
-                        .min_by(|a, b| a.partial_cmp(b).unwrap())
-                        .unwrap();
+        let sweep = &self.scale_sweep_heat_map;
+        let (Some(&min_length), Some(&max_length)) = (sweep.lengths.first(), sweep.lengths.last())
+        else {
+            return;
+        };
+        let rows = sweep.lengths.len();
+        let row_height = map_rect.height() / rows as f32;
+        let pos_to_x = |pos: f32| map_rect.left() + (pos / max_length) * map_rect.width();
+        let row_center = |i: usize| map_rect.top() + (i as f32 + 0.5) * row_height;
+
+        // Scale length labels at the top and bottom rows
+        for (i, length) in [(0, min_length), (rows - 1, max_length)] {
+            painter.text(
+                Pos2::new(map_rect.left() + 4.0, row_center(i)),
+                egui::Align2::LEFT_CENTER,
+                self.unit.format(length),
+                egui::FontId::proportional(10.0),
+                Color32::WHITE,
+            );
+        }
 
-                    // Sine wave falloff: use cosine for smooth bell curve
-                    // The wavelength determines how far the influence extends
-                    let wavelength = self.string_length / (harmonic as f32 * 2.0);
-                    let normalized_dist = (min_dist / wavelength).min(1.0);
-                    let falloff = (normalized_dist * std::f32::consts::PI / 2.0).cos();
+        // Trace the optimum curves
+        draw_optimum_traces(
+            painter,
+            map_rect,
+            &sweep.optima,
+            pos_to_x,
+            row_center,
+            false,
+        );
 
-                    weight * falloff
-                })
-                .sum();
+        // Nut line for each scale length runs diagonally across the map
+        painter.line_segment(
+            [
+                Pos2::new(pos_to_x(min_length), row_center(0)),
+                Pos2::new(pos_to_x(max_length), row_center(rows - 1)),
+            ],
+            Stroke::new(1.0, Color32::from_gray(160)),
+        );
 
-            *heat = total_heat;
+        // Draw bridge and nut labels
+        painter.text(
+            Pos2::new(map_rect.left(), rect.max.y - BOTTOM_PADDING),
+            egui::Align2::CENTER_BOTTOM,
+            "Bridge",
+            egui::FontId::proportional(12.0),
+            Color32::WHITE,
+        );
+
+        painter.text(
+            Pos2::new(map_rect.right(), rect.max.y - BOTTOM_PADDING),
+            egui::Align2::RIGHT_BOTTOM,
+            self.unit.format(max_length),
+            egui::FontId::proportional(12.0),
+            Color32::WHITE,
+        );
+    }
+}
+
+/// Connect the per-row bridge/neck optima with a line, labelled above the map
+fn draw_optimum_traces(
+    painter: &egui::Painter,
+    map_rect: egui::Rect,
+    optima: &[OptimalPositions],
+    pos_to_x: impl Fn(f32) -> f32,
+    row_center: impl Fn(usize) -> f32,
+    show_points: bool,
+) {
+    let bridge_color = Color32::parse_hex(0xB57EDC);
+    let neck_color = Color32::parse_hex(0xB266FF);
+
+    let trace = |position: fn(&OptimalPositions) -> f32| -> Vec<Pos2> {
+        optima
+            .iter()
+            .enumerate()
+            .map(|(i, optimum)| Pos2::new(pos_to_x(position(optimum)), row_center(i)))
+            .collect()
+    };
+
+    for (points, color, label) in [
+        (trace(|o| o.bridge_position), bridge_color, "Bridge"),
+        (trace(|o| o.neck_position), neck_color, "Neck"),
+    ] {
+        if let Some(&first) = points.first() {
+            painter.text(
+                Pos2::new(first.x, map_rect.top() - LABEL_HEIGHT - 15.0),
+                egui::Align2::CENTER_BOTTOM,
+                label,
+                egui::FontId::proportional(11.0),
+                color,
+            );
+        }
+        if show_points {
+            for &point in &points {
+                painter.circle_filled(point, 3.0, color);
+            }
         }
+        painter.add(egui::Shape::line(points, Stroke::new(2.0, color)));
+    }
+}
 
-        heat_map
+/// Axis across `x_range` showing `values` (at the left and right ends), with tick spacing
+/// chosen so labels stay readable at any zoom. `unit` is written in the right-hand gutter.
+fn draw_axis(
+    painter: &egui::Painter,
+    x_range: std::ops::RangeInclusive<f32>,
+    y: f32,
+    values: (f32, f32),
+    unit: &str,
+) {
+    const MIN_LABEL_SPACING: f32 = 60.0; // px between labelled ticks
+
+    let (start_x, end_x) = (*x_range.start(), *x_range.end());
+    let (start_value, end_value) = values;
+    let width = end_x - start_x;
+    if width <= 0.0 || end_value <= start_value {
+        return;
     }
+    let to_x = |value: f32| start_x + (value - start_value) / (end_value - start_value) * width;
+
+    painter.line_segment(
+        [Pos2::new(start_x, y), Pos2::new(end_x, y)],
+        Stroke::new(1.0, Color32::from_gray(120)),
+    );
+
+    painter.text(
+        Pos2::new(end_x + 6.0, y),
+        egui::Align2::LEFT_CENTER,
+        unit,
+        egui::FontId::proportional(10.0),
+        Color32::from_gray(150),
+    );
+
+    let step = nice_step((end_value - start_value) * MIN_LABEL_SPACING / width);
+    let minor_step = step / 5.0;
+    let first = (start_value / minor_step).ceil() as i64;
+    let last = (end_value / minor_step).floor() as i64;
+    for i in first..=last {
+        let value = i as f32 * minor_step;
+        let x = to_x(value);
+        let major = i.rem_euclid(5) == 0;
+        painter.line_segment(
+            [
+                Pos2::new(x, y),
+                Pos2::new(x, y + if major { 6.0 } else { 3.0 }),
+            ],
+            Stroke::new(1.0, Color32::from_gray(if major { 170 } else { 100 })),
+        );
+        if major {
+            painter.text(
+                Pos2::new(x, y + 7.0),
+                egui::Align2::CENTER_TOP,
+                format_tick(value, step),
+                egui::FontId::proportional(9.0),
+                Color32::from_gray(170),
+            );
+        }
+    }
+}
+
+/// Smallest 1, 2 or 5 times a power of ten that is at least `raw`
+fn nice_step(raw: f32) -> f32 {
+    let magnitude = 10.0_f32.powf(raw.max(f32::EPSILON).log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|&step| step >= raw)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Only as many decimals as the tick step needs
+fn format_tick(value: f32, step: f32) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    format!("{:.*}", decimals, value)
 }